@@ -0,0 +1,170 @@
+use std::fmt::Write;
+use std::io::BufRead;
+use std::rc::Rc;
+use clap::ValueEnum;
+use crate::floats::{Float, FloatParameters};
+use crate::printers::{print_error, visible_len, Printer, BOLD, RED, RESET};
+
+/// How the results of a batch are written out.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum BatchFormat {
+  /// An aligned table, with the first line of each printer's output in a column.
+  #[default]
+  Table,
+  /// One JSON object per input line.
+  Json,
+}
+
+/// The outcome of one input line.
+struct Record {
+  line: usize,
+  input: String,
+  result: Result<(FloatParameters, Vec<Vec<String>>), String>,
+}
+
+/// Parses a line that contains either a value, or a type followed by a value.
+fn parse_line(line: &str, default_type: Option<&FloatParameters>) -> Result<Float, String> {
+  let (type_, value) = match line.rsplit_once(char::is_whitespace) {
+    Some((type_, value)) => (Some(type_.trim()), value),
+    None => (None, line),
+  };
+  let ftype = match type_ {
+    Some(type_) => FloatParameters::parse(type_).map_err(|e| format!("Error parsing type \"{}\": {}", type_, e))?,
+    None => default_type.cloned().ok_or("No type given, and no default type set with --type")?,
+  };
+  Float::parse(value, &ftype).map_err(|e| format!("Error parsing float \"{}\": {}", value, e))
+}
+
+/// Reads values line by line and prints each one using `printers`.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn run_batch(input: impl BufRead, default_type: Option<&FloatParameters>, format: BatchFormat, printers: &[(String, Rc<dyn Printer>)]) {
+  let mut records = Vec::new();
+  for (i, line) in input.lines().enumerate() {
+    let line = match line {
+      Ok(line) => line,
+      Err(e) => {
+        print_error(format_args!("Error reading input: {}", e));
+        break;
+      }
+    };
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+      continue;
+    }
+    let result = parse_line(trimmed, default_type).map(|v| {
+      (v.params().clone(), printers.iter().map(|(_, p)| p.print(&v)).collect())
+    });
+    let record = Record { line: i + 1, input: trimmed.to_owned(), result };
+    match format {
+      BatchFormat::Json => println!("{}", record_to_json(&record, printers)),
+      BatchFormat::Table => records.push(record),
+    }
+  }
+  if format == BatchFormat::Table {
+    print_table(&records, printers);
+  }
+}
+
+fn print_table(records: &[Record], printers: &[(String, Rc<dyn Printer>)]) {
+  let mut rows = vec![
+    ["Line", "Input", "Type"].iter().map(|v| v.to_string())
+      .chain(printers.iter().map(|(_, p)| p.name().to_owned()))
+      .collect::<Vec<_>>()
+  ];
+  for record in records {
+    let mut row = vec![record.line.to_string(), record.input.clone()];
+    match &record.result {
+      Ok((ftype, outputs)) => {
+        row.push(ftype.to_string());
+        row.extend(outputs.iter().map(|v| v.first().cloned().unwrap_or_default()));
+      }
+      Err(e) => {
+        row.push(String::new());
+        row.push(format!("{}{}{}", RED, e, RESET));
+      }
+    }
+    rows.push(row);
+  }
+
+  let columns = rows.iter().map(|v| v.len()).max().unwrap_or(0);
+  let widths = (0..columns).map(|i| {
+    rows.iter().filter_map(|v| v.get(i)).map(|v| visible_len(v)).max().unwrap_or(0)
+  }).collect::<Vec<_>>();
+  for (i, row) in rows.iter().enumerate() {
+    let mut s = String::new();
+    for (j, cell) in row.iter().enumerate() {
+      // errors may be wider than the column they start in
+      let pad = if j == row.len() - 1 { 0 } else { widths[j].saturating_sub(visible_len(cell)) + 2 };
+      if i == 0 {
+        write!(s, "{}{}{}", BOLD, cell, RESET).unwrap();
+      } else {
+        s.push_str(cell);
+      }
+      s.push_str(&" ".repeat(pad));
+    }
+    println!("{}", s);
+  }
+}
+
+fn record_to_json(record: &Record, printers: &[(String, Rc<dyn Printer>)]) -> String {
+  let mut s = format!("{{\"line\":{},\"input\":{}", record.line, json_string(&record.input));
+  match &record.result {
+    Ok((ftype, outputs)) => {
+      write!(s, ",\"type\":{},\"printers\":{{", json_string(&ftype.to_string())).unwrap();
+      for (i, ((name, _), lines)) in printers.iter().zip(outputs).enumerate() {
+        let lines = lines.iter().map(|v| json_string(v)).collect::<Vec<_>>().join(",");
+        write!(s, "{}{}:[{}]", if i == 0 { "" } else { "," }, json_string(name), lines).unwrap();
+      }
+      s.push('}');
+    }
+    Err(e) => write!(s, ",\"error\":{}", json_string(e)).unwrap(),
+  }
+  s.push('}');
+  s
+}
+
+fn json_string(s: &str) -> String {
+  let mut out = String::from("\"");
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::floats::{F32_PARAMS, F64_PARAMS};
+  use crate::printers::human::ExactDecimalPrinter;
+
+  #[test]
+  fn parse_line_with_and_without_type() {
+    assert_eq!(parse_line("f32 0.5", None).unwrap().params(), &F32_PARAMS);
+    assert_eq!(parse_line("custom(5, 2) 1", None).unwrap().params(), &FloatParameters::new(5, 2));
+    assert_eq!(parse_line("0.5", Some(&F64_PARAMS)).unwrap().params(), &F64_PARAMS);
+    assert!(parse_line("0.5", None).is_err());
+    assert!(parse_line("f33 1", None).unwrap_err().starts_with("Error parsing type \"f33\": Unknown type"));
+    assert!(parse_line("f32 one", None).unwrap_err().starts_with("Error parsing float \"one\""));
+  }
+
+  #[test]
+  fn json_lines() {
+    let printers: Vec<(String, Rc<dyn Printer>)> = vec![("exact".into(), Rc::new(ExactDecimalPrinter))];
+    let ok = Record { line: 1, input: "f32 0.5".into(), result: Ok((F32_PARAMS, vec![vec![".5".into()]])) };
+    assert_eq!(record_to_json(&ok, &printers), r#"{"line":1,"input":"f32 0.5","type":"f32","printers":{"exact":[".5"]}}"#);
+    let err = Record { line: 2, input: "x".into(), result: Err("Error parsing float \"x\"".into()) };
+    assert_eq!(record_to_json(&err, &printers), r#"{"line":2,"input":"x","error":"Error parsing float \"x\""}"#);
+  }
+
+  #[test]
+  fn json_string_escapes() {
+    assert_eq!(json_string("a\"b\\c\nd\u{1b}"), r#""a\"b\\c\nd\u001b""#);
+  }
+}
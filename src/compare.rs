@@ -0,0 +1,90 @@
+use std::cmp::Ordering;
+use num_bigint::BigUint;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+use crate::eval::format_real;
+use crate::floats::{pow2, Float};
+use crate::printers::{BOLD, DARK_GRAY, RESET};
+use crate::str_conv::rational_to_sci_str;
+
+/// Returns the exponent of the leading bit of a positive value, i.e. floor(log2(value)).
+fn floor_log2(value: &BigRational) -> i64 {
+  let mut exp = value.numer().bits() as i64 - value.denom().bits() as i64;
+  if value < &pow2(exp) {
+    exp -= 1;
+  }
+  exp
+}
+
+/// Counts the leading significand bits that two values have in common, out of the larger precision.
+/// Values with different signs or leading bits in different positions have none in common.
+fn matching_bits(a: &Float, b: &Float) -> (u64, u64) {
+  let precision = a.params().sig_bits.max(b.params().sig_bits) as u64 + 1;
+  let (Some(x), Some(y)) = (a.exact_signed_value(), b.exact_signed_value()) else {
+    return (0, precision);
+  };
+  if x == y {
+    return (precision, precision);
+  }
+  if x.is_zero() || y.is_zero() || x.is_negative() != y.is_negative() {
+    return (0, precision);
+  }
+  let (x, y) = (x.abs(), y.abs());
+  let exp = floor_log2(&x);
+  if exp != floor_log2(&y) {
+    return (0, precision);
+  }
+  // scale both to integers with the leading bit at position precision - 1
+  let scale = pow2(precision as i64 - 1 - exp);
+  let to_int = |v: &BigRational| -> BigUint { (v * &scale).floor().to_integer().magnitude().clone() };
+  let diff = to_int(&x) ^ to_int(&y);
+  (precision.saturating_sub(diff.bits()), precision)
+}
+
+/// Formats a distance as a number of ULPs of `val`.
+fn ulp_distance(diff: &BigRational, val: &Float) -> String {
+  if !val.classify().finite() {
+    return "undefined".to_owned();
+  }
+  let ulp = pow2(val.exponent_logical() - val.params().sig_bits as i64);
+  let ulps = diff.abs() / ulp;
+  match ulps.to_f64() {
+    Some(v) if v < 1e6 => format!("{:.4}", v),
+    _ => rational_to_sci_str(&ulps, 6),
+  }
+}
+
+/// Prints the comparison predicates and distances between two floats.
+pub fn print_comparison(a: &Float, b: &Float) {
+  let predicate = |name: &str, value: bool| println!("{:<18}{}", name, value);
+  let ordering = a.compare(b);
+  println!("{}Predicates{}", BOLD, RESET);
+  predicate("A < B", ordering == Some(Ordering::Less));
+  predicate("A == B", ordering == Some(Ordering::Equal));
+  predicate("A > B", ordering == Some(Ordering::Greater));
+  predicate("unordered", ordering.is_none());
+  predicate("totalOrder(A, B)", a.total_cmp(b) != Ordering::Greater);
+  if a.params() != b.params() {
+    println!("{}totalOrder is only defined within a format, NaNs of the same sign compare equal here{}", DARK_GRAY, RESET);
+  }
+  println!();
+
+  println!("{}Distance{}", BOLD, RESET);
+  let (Some(x), Some(y)) = (a.exact_signed_value(), b.exact_signed_value()) else {
+    println!("Undefined, at least one value is not finite");
+    return;
+  };
+  let diff = &y - &x;
+  println!("{:<22}{}", "B - A", format_real(&diff));
+  if !x.is_zero() {
+    println!("{:<22}{}", "(B - A) / |A|", rational_to_sci_str(&(&diff / x.abs()), 6));
+  }
+  println!("{:<22}{}", format!("ULPs of A ({})", a.params()), ulp_distance(&diff, a));
+  println!("{:<22}{}", format!("ULPs of B ({})", b.params()), ulp_distance(&diff, b));
+  if a.params() == b.params() {
+    let steps = b.ordered_integer() - a.ordered_integer();
+    println!("{:<22}{} {}(count of floats from A to B){}", "Steps", steps, DARK_GRAY, RESET);
+  }
+  let (matching, precision) = matching_bits(a, b);
+  println!("{:<22}{} of {}", "Matching leading bits", matching, precision);
+}
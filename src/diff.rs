@@ -0,0 +1,135 @@
+use num_bigint::BigInt;
+use num_traits::One;
+use crate::floats::{bits_to_biguint, BitVec, Float};
+use crate::printers::binary::{bit_column, print_float, print_guide_markers};
+use crate::printers::{BOLD, RED, RESET};
+
+/// Formats a list of bit indices with a verb, e.g. `bits 3, 5 and 6 differ`.
+fn format_bits(bits: &[usize]) -> String {
+  match bits {
+    [bit] => format!("bit {} differs", bit),
+    [rest @ .., last] => format!("bits {} and {} differ", rest.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "), last),
+    [] => String::new(),
+  }
+}
+
+/// Explains what the differences in each field of `a` and `b` do to the value.
+fn explain(a: &Float, b: &Float, diff: &BitVec) -> Vec<String> {
+  let params = a.params();
+  let all_ones = (1u64 << params.exp_bits) - 1;
+  let (ea, eb) = (a.exponent_bits_integer(), b.exponent_bits_integer());
+  let mut lines = Vec::new();
+
+  if diff[params.total_length() - 1] {
+    lines.push(format!("sign bit {} flipped → value negated", params.total_length() - 1));
+  }
+
+  let exp_bits = diff.iter_ones().filter(|&i| i >= params.sig_bits && i < params.total_length() - 1).collect::<Vec<_>>();
+  let sig_bits = diff.iter_ones().filter(|&i| i < params.sig_bits).collect::<Vec<_>>();
+  if !exp_bits.is_empty() {
+    let effect = if eb == all_ones {
+      "B is infinite or NaN".to_owned()
+    } else if ea == all_ones {
+      "A is infinite or NaN".to_owned()
+    } else if eb == 0 {
+      "B is subnormal or zero".to_owned()
+    } else if ea == 0 {
+      "A is subnormal or zero".to_owned()
+    } else {
+      let d = eb as i64 - ea as i64;
+      // the sign and significand change the value as well, so only the exponent's share is known
+      let only_exponent = sig_bits.is_empty() && !diff[params.total_length() - 1];
+      match (d, only_exponent) {
+        (1, true) => "value doubled".to_owned(),
+        (-1, true) => "value halved".to_owned(),
+        (_, true) => format!("value scaled by 2^{}", d),
+        (1, false) => "the exponent alone doubles the magnitude".to_owned(),
+        (-1, false) => "the exponent alone halves the magnitude".to_owned(),
+        (_, false) => format!("the exponent alone scales the magnitude by 2^{}", d),
+      }
+    };
+    lines.push(format!("exponent {}, {:+} → {}", format_bits(&exp_bits), eb as i64 - ea as i64, effect));
+  }
+
+  if !sig_bits.is_empty() {
+    let effect = if a.classify().nan() && b.classify().nan() {
+      if sig_bits.contains(&(params.sig_bits - 1)) {
+        format!("quiet bit flipped, B is a {} NaN", if b.significand_bits()[params.sig_bits - 1] { "quiet" } else { "signalling" })
+      } else {
+        "NaN payload changed".to_owned()
+      }
+    } else if ea == eb && ea != all_ones {
+      // with the same exponent, the significand difference is a number of ULPs
+      let delta = BigInt::from(bits_to_biguint(b.significand_bits())) - BigInt::from(bits_to_biguint(a.significand_bits()));
+      let unit = if delta.magnitude().is_one() { "ULP" } else { "ULPs" };
+      let lsb = if sig_bits == [0] { "LSB flipped, " } else { "" };
+      format!("{}{:+} {}", lsb, delta, unit)
+    } else {
+      "the exponent differs too, so the change is not a whole number of ULPs".to_owned()
+    };
+    lines.push(format!("significand {} → {}", format_bits(&sig_bits), effect));
+  }
+
+  if lines.is_empty() {
+    lines.push("the encodings are identical".to_owned());
+  }
+  lines
+}
+
+/// Prints the encodings of two floats of the same format above each other, with the differing bits marked.
+pub fn print_diff(a: &Float, b: &Float) {
+  let params = a.params();
+  let diff = a.bits().to_bitvec() ^ b.bits();
+  let mut guide = String::new();
+  let mut line_a = String::new();
+  let mut line_b = String::new();
+  print_guide_markers(&mut guide, params).unwrap();
+  print_float(&mut line_a, a, Some(&diff)).unwrap();
+  print_float(&mut line_b, b, Some(&diff)).unwrap();
+
+  let mut markers = vec![' '; bit_column(params, 0) + 1];
+  diff.iter_ones().for_each(|i| markers[bit_column(params, i)] = '^');
+  let markers = markers.iter().collect::<String>().trim_end().to_owned();
+
+  println!("   {}", guide);
+  println!("A: {}", line_a);
+  println!("B: {}", line_b);
+  println!("   {}{}{}", RED, markers, RESET);
+  println!();
+  println!("{}{} of {} bits differ{}", BOLD, diff.count_ones(), params.total_length(), RESET);
+  explain(a, b, &diff).iter().for_each(|v| println!("{}", v));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::floats::F32_PARAMS;
+
+  fn explain_f32(a: &str, b: &str) -> Vec<String> {
+    let (a, b) = (Float::parse(a, &F32_PARAMS).unwrap(), Float::parse(b, &F32_PARAMS).unwrap());
+    explain(&a, &b, &(a.bits().to_bitvec() ^ b.bits()))
+  }
+
+  #[test]
+  fn explains_each_field() {
+    assert_eq!(explain_f32("1", "-1"), ["sign bit 31 flipped → value negated"]);
+    assert_eq!(explain_f32("2", "4"), ["exponent bit 23 differs, +1 → value doubled"]);
+    assert_eq!(explain_f32("1", "0.25"), ["exponent bit 24 differs, -2 → value scaled by 2^-2"]);
+    // 2 → 5 changes the significand as well, so the value isn't simply doubled
+    assert_eq!(explain_f32("2", "5"), [
+      "exponent bit 23 differs, +1 → the exponent alone doubles the magnitude",
+      "significand bit 21 differs → the exponent differs too, so the change is not a whole number of ULPs",
+    ]);
+    assert_eq!(explain_f32("2", "-4")[1], "exponent bit 23 differs, +1 → the exponent alone doubles the magnitude");
+    assert_eq!(explain_f32("1", "0x3f800001"), ["significand bit 0 differs → LSB flipped, +1 ULP"]);
+    assert_eq!(explain_f32("0x3f800003", "1"), ["significand bits 0 and 1 differ → -3 ULPs"]);
+    assert_eq!(explain_f32("1", "1"), ["the encodings are identical"]);
+  }
+
+  #[test]
+  fn special_values() {
+    assert_eq!(explain_f32("0x7f800001", "0x7fc00001"), ["significand bit 22 differs → quiet bit flipped, B is a quiet NaN"]);
+    assert_eq!(explain_f32("0x7fc00001", "0x7fc00002"), ["significand bits 0 and 1 differ → NaN payload changed"]);
+    assert_eq!(explain_f32("0x00000001", "0x00800001")[0], "exponent bit 23 differs, +1 → A is subnormal or zero");
+  }
+}
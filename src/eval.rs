@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+use thiserror::Error;
+use crate::expr::Expr;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{pow2, Float, FloatParameters, FloatParseError};
+use crate::ints::{IntType, Integer};
+use crate::ops::{Exception, Kind, Op, Value};
+use crate::printers::human::ExactDecimalPrinter;
+use crate::printers::{Printer, BOLD, DARK_GRAY, RESET};
+use crate::str_conv::{parse_decimal, rational_to_exact_str, rational_to_sci_str, DecimalLiteral};
+
+#[derive(Error, Debug)]
+pub enum EvalError {
+  #[error("Error parsing float \"{0}\": {1}")]
+  Float(String, FloatParseError),
+  #[error("Unknown variable: {0}")]
+  UnknownVariable(String),
+  #[error("Unknown operation: {0}")]
+  UnknownOp(String),
+  #[error("Wrong number of arguments to {0}, expected {1}")]
+  WrongArgCount(String, usize),
+  #[error("Invalid setting for {0}: {1}")]
+  InvalidSetting(String, String),
+  #[error("A type is only allowed as the last argument of an operation")]
+  MisplacedType,
+  #[error("{0} is not a float and can't be used as an operand")]
+  NotAFloat(String),
+  #[error("{0} is not an integer and can't be used as an integer operand")]
+  NotAnInteger(String),
+}
+
+/// One evaluated node of an expression.
+pub struct Step {
+  pub expr: Expr,
+  pub value: Value,
+  /// The exact result of this step before rounding, if it is known.
+  pub exact: Option<BigRational>,
+  /// The value of the whole subexpression if it was computed without any rounding.
+  pub real: Option<BigRational>,
+  /// Exceptions raised by this step alone.
+  pub exception: Exception,
+  /// The name of the operation and its steps, if tracing was requested.
+  pub trace: Option<(String, String)>,
+  pub children: Vec<Step>,
+}
+
+impl Step {
+  /// Returns the exceptions raised by this step and all steps below it.
+  pub fn all_exceptions(&self) -> Exception {
+    self.children.iter().fold(self.exception, |acc, v| acc | v.all_exceptions())
+  }
+
+  /// The rounding error introduced by this step.
+  pub fn step_error(&self) -> Option<BigRational> {
+    Some(self.value.float()?.exact_signed_value()? - self.exact.as_ref()?)
+  }
+
+  /// The error of this step compared to the exact real-number result.
+  pub fn total_error(&self) -> Option<BigRational> {
+    Some(self.value.float()?.exact_signed_value()? - self.real.as_ref()?)
+  }
+}
+
+/// Evaluates expressions, rounding after every operation.
+pub struct Evaluator<'a> {
+  pub ops: &'a BTreeMap<String, Rc<dyn Op>>,
+  pub env: &'a FloatingPointEnv,
+  pub vars: &'a BTreeMap<String, Float>,
+  /// The format of literals without a type.
+  pub literal_type: &'a FloatParameters,
+  /// The format of results. If unset, results have the format of their first operand.
+  pub output_type: Option<&'a FloatParameters>,
+  pub trace: bool,
+}
+
+impl Evaluator<'_> {
+  pub fn eval(&self, expr: &Expr) -> Result<Step, EvalError> {
+    match expr {
+      Expr::Literal(s) => self.literal(expr, s, self.literal_type),
+      Expr::Typed(ty, s) => self.literal(expr, s, ty),
+      Expr::Type(_) => Err(EvalError::MisplacedType),
+      Expr::Var(name) => {
+        let value = self.vars.get(name).ok_or_else(|| EvalError::UnknownVariable(name.clone()))?;
+        let exact = value.exact_signed_value();
+        Ok(Step { expr: expr.clone(), value: value.clone().into(), exact: exact.clone(), real: exact, exception: Exception::default(), trace: None, children: vec![] })
+      }
+      Expr::Neg(operand) => self.op(expr, "neg", std::slice::from_ref(operand)),
+      Expr::Binary(op, lhs, rhs) => self.op(expr, op.op_name(), &[lhs.as_ref().clone(), rhs.as_ref().clone()]),
+      Expr::Call(name, args) => self.op(expr, name, args),
+    }
+  }
+
+  fn literal(&self, expr: &Expr, s: &str, ty: &FloatParameters) -> Result<Step, EvalError> {
+    let (value, exact, exception) = match parse_decimal(s) {
+      Some(DecimalLiteral::Finite(sign, v)) => {
+        let (value, exception) = self.env.round(&mut String::new(), sign, &v, ty).unwrap();
+        (value, Some(if sign { -v } else { v }), exception)
+      }
+      _ => {
+        let value = Float::parse_in_env(s, ty, self.env).map_err(|e| EvalError::Float(s.to_owned(), e))?;
+        let exact = value.exact_signed_value();
+        (value, exact, Exception::default())
+      }
+    };
+    Ok(Step { expr: expr.clone(), value: value.into(), exact: exact.clone(), real: exact, exception, trace: None, children: vec![] })
+  }
+
+  /// Evaluates the operands of an operation, then applies it to their rounded values.
+  /// A trailing type selects the output format, and settings such as those of `to-int` follow the operands.
+  fn op(&self, expr: &Expr, name: &str, args: &[Expr]) -> Result<Step, EvalError> {
+    let op = self.ops.get(name).ok_or_else(|| EvalError::UnknownOp(name.to_owned()))?;
+    // a trailing type selects the output format
+    let (args, output_type) = match args.split_last() {
+      Some((Expr::Type(ty), rest)) => (rest, Some(ty)),
+      _ => (args, None),
+    };
+    if args.len() < op.num_params() || args.len() > op.num_params() + op.settings().len() {
+      return Err(EvalError::WrongArgCount(name.to_owned(), op.num_params()));
+    }
+    // settings such as the integer type of to-int follow the operands, as names
+    let (args, settings) = args.split_at(op.num_params());
+    let op = if settings.is_empty() {
+      op.clone()
+    } else {
+      let settings = settings.iter().map(|v| match v {
+        Expr::Var(setting) => Ok(setting.clone()),
+        v => Err(EvalError::InvalidSetting(name.to_owned(), format!("expected a name, got {}", v))),
+      }).collect::<Result<Vec<_>, _>>()?;
+      op.with_settings(&settings).map_err(|e| EvalError::InvalidSetting(name.to_owned(), e))?
+    };
+
+    let children = args.iter().map(|v| self.eval(v)).collect::<Result<Vec<_>, _>>()?;
+    let params = children.iter().zip(op.param_kinds()).map(|(v, kind)| match (kind, &v.value) {
+      (Kind::Float, Value::Float(_)) | (Kind::Int, Value::Int(_)) => Ok(v.value.clone()),
+      (Kind::Int, Value::Float(f)) => integral(f).map(Value::Int).ok_or_else(|| EvalError::NotAnInteger(v.expr.to_string())),
+      (Kind::Int, _) => Err(EvalError::NotAnInteger(v.expr.to_string())),
+      (Kind::Float, _) => Err(EvalError::NotAFloat(v.expr.to_string())),
+    }).collect::<Result<Vec<_>, _>>()?;
+    let output_type = output_type
+      .or(self.output_type)
+      .or(params.iter().find_map(|v| v.float()).map(|v| v.params()))
+      .unwrap_or(self.literal_type);
+
+    let (value, exception, trace) = if self.trace {
+      let mut s = String::new();
+      let (value, exception) = op.execute_visual(&mut s, self.env, &params, output_type).unwrap();
+      (value, exception, Some((name.to_owned(), s)))
+    } else {
+      let (value, exception) = op.execute(self.env, &params, output_type);
+      (value, exception, None)
+    };
+    let exact = params.iter().map(|v| v.float()?.exact_signed_value()).collect::<Option<Vec<_>>>()
+      .and_then(|v| exact_op(name, &v));
+    let real = children.iter().map(|v| v.real.clone()).collect::<Option<Vec<_>>>()
+      .and_then(|v| exact_op(name, &v));
+    Ok(Step { expr: expr.clone(), value, exact, real, exception, trace, children })
+  }
+}
+
+/// Computes the result of an operation without rounding, for the operations where this is possible.
+fn exact_op(name: &str, args: &[BigRational]) -> Option<BigRational> {
+  match (name, args) {
+    ("add", [a, b]) => Some(a + b),
+    ("sub", [a, b]) => Some(a - b),
+    ("mul", [a, b]) => Some(a * b),
+    ("div", [a, b]) if !b.is_zero() => Some(a / b),
+    ("convert", [a]) => Some(a.clone()),
+    ("neg", [a]) => Some(-a),
+    _ => None,
+  }
+}
+
+/// Formats an error, along with its size relative to the ULP of the value.
+pub fn format_error(err: &BigRational, value: &Float) -> String {
+  let mut s = rational_to_sci_str(err, 6);
+  if !err.is_zero() && !s.starts_with('-') {
+    s.insert(0, '+');
+  }
+  if value.classify().finite() {
+    let ulp = pow2(value.exponent_logical() - value.params().sig_bits as i64);
+    match (err / ulp).to_f64() {
+      Some(ulps) if ulps.abs() < 1e6 => s += &format!(" ({:+.4} ULP)", ulps),
+      Some(ulps) => s += &format!(" ({:+.3e} ULP)", ulps),
+      None => {}
+    }
+  }
+  s
+}
+
+/// Formats an exact value, abbreviating it if it has no short decimal expansion.
+pub fn format_real(value: &BigRational) -> String {
+  const MAX_EXACT_LEN: usize = 80;
+  // the expansion has about log10(value) digits before the point and at least one digit after it
+  // for each factor 2 of the denominator, so values that are far too long aren't expanded at all
+  let (num, den) = (value.numer().magnitude(), value.denom().magnitude());
+  let int_digits = (num.bits() as f64 - den.bits() as f64) * 2f64.log10();
+  let frac_digits = den.trailing_zeros().unwrap_or(0);
+  if int_digits <= MAX_EXACT_LEN as f64 && frac_digits <= MAX_EXACT_LEN as u64 {
+    match rational_to_exact_str(value) {
+      Some(s) if s.len() <= MAX_EXACT_LEN => return s,
+      _ => {}
+    }
+  }
+  format!("≈ {}", rational_to_sci_str(value, 30))
+}
+
+/// Uses an integral float as an integer operand, typed as i64 unless it needs more bits.
+fn integral(value: &Float) -> Option<Integer> {
+  let value = value.exact_signed_value().filter(|v| v.is_integer())?.to_integer();
+  let bits = (value.bits() as usize + 1).max(64);
+  Some(Integer { value, ty: IntType { bits, signed: true } })
+}
+
+/// Formats a result on one line.
+fn format_value(value: &Value) -> String {
+  match value {
+    Value::Float(v) => ExactDecimalPrinter.print(v).remove(0),
+    Value::Bool(v) => v.to_string(),
+    Value::Int(v) => format!("{} ({})", v.value, v.ty),
+    Value::Tuple(values) => format!("({})", values.iter().map(|(name, v)| format!("{}: {}", name, format_value(v))).collect::<Vec<_>>().join(", ")),
+    Value::Undefined(v) => v.clone(),
+  }
+}
+
+/// Prints a step and all steps below it as a tree.
+pub fn print_tree(step: &Step, prefix: &str, connector: &str, child_prefix: &str) {
+  let value = format_value(&step.value);
+  println!("{}{}{}{}{} = {}", prefix, connector, BOLD, step.expr, RESET, value);
+  let detail_prefix = format!("{}{}{}", prefix, child_prefix, if step.children.is_empty() { "   " } else { "│  " });
+  if let Value::Float(value) = &step.value {
+    if let Some(err) = step.step_error().filter(|v| !v.is_zero()) {
+      println!("{}{}rounding error: {}{}", detail_prefix, DARK_GRAY, format_error(&err, value), RESET);
+    }
+    if !step.children.is_empty() {
+      if let Some(err) = step.total_error().filter(|v| !v.is_zero()) {
+        println!("{}{}error vs. real: {}{}", detail_prefix, DARK_GRAY, format_error(&err, value), RESET);
+      }
+    }
+  }
+  if step.exception != Exception::default() {
+    println!("{}{}flags: {}{}", detail_prefix, DARK_GRAY, step.exception, RESET);
+  }
+  let next_prefix = format!("{}{}", prefix, child_prefix);
+  for (i, child) in step.children.iter().enumerate() {
+    let last = i == step.children.len() - 1;
+    print_tree(child, &next_prefix, if last { "└─ " } else { "├─ " }, if last { "   " } else { "│  " });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use num_bigint::BigInt;
+  use super::*;
+  use crate::expr::parse_expr;
+  use crate::fenv::RoundingMode;
+  use crate::floats::{F32_PARAMS, F64_PARAMS};
+  use crate::ops::collect_ops;
+
+  fn eval(s: &str, env: &FloatingPointEnv, vars: &BTreeMap<String, Float>) -> Result<Step, EvalError> {
+    let ops = collect_ops();
+    let evaluator = Evaluator { ops: &ops, env, vars, literal_type: &F64_PARAMS, output_type: None, trace: false };
+    evaluator.eval(&parse_expr(s).unwrap())
+  }
+
+  fn ratio(n: i64, d: i64) -> BigRational {
+    BigRational::new(BigInt::from(n), BigInt::from(d))
+  }
+
+  #[test]
+  fn rounds_after_every_step() {
+    let step = eval("0.1 + 0.2", &FloatingPointEnv::default(), &BTreeMap::new()).unwrap();
+    assert_eq!(step.value.float().unwrap().exact_signed_value(), Float::parse("0.30000000000000004", &F64_PARAMS).unwrap().exact_signed_value());
+    assert_eq!(step.real, Some(ratio(3, 10)));
+    // the exact result of a step is computed from its rounded operands
+    assert_eq!(step.exact, step.children[0].value.float().unwrap().exact_signed_value().zip(step.children[1].value.float().unwrap().exact_signed_value()).map(|(a, b)| a + b));
+    assert!(step.step_error().is_some_and(|v| !v.is_zero()));
+    assert!(step.all_exceptions().contains(Exception::INEXACT));
+  }
+
+  #[test]
+  fn types_select_formats() {
+    let step = eval("f32 0.1 * 3", &FloatingPointEnv::default(), &BTreeMap::new()).unwrap();
+    assert_eq!(step.value.float().unwrap().params(), &F32_PARAMS);
+    let step = eval("convert(1 / 3, f32)", &FloatingPointEnv::default(), &BTreeMap::new()).unwrap();
+    assert_eq!(step.value.float().unwrap().params(), &F32_PARAMS);
+    assert_eq!(step.real, Some(ratio(1, 3)));
+  }
+
+  #[test]
+  fn honours_the_rounding_mode() {
+    let down = FloatingPointEnv { rounding_mode: RoundingMode::TowardZero, ..Default::default() };
+    let up = FloatingPointEnv { rounding_mode: RoundingMode::TowardPositive, ..Default::default() };
+    let third = |env| eval("f32 1 / 3", env, &BTreeMap::new()).unwrap().value.float().unwrap().exact_signed_value().unwrap();
+    assert!(third(&down) < ratio(1, 3));
+    assert!(third(&up) > ratio(1, 3));
+    // the literal is rounded up before it is negated
+    for s in ["-(f32 0.1)", "-f32 0.1"] {
+      let step = eval(s, &up, &BTreeMap::new()).unwrap();
+      assert_eq!(step.value.float().unwrap().exact_signed_value(), Float::parse("-0.100000001", &F32_PARAMS).unwrap().exact_signed_value());
+    }
+    let step = eval("-(0.1)", &up, &BTreeMap::new()).unwrap();
+    assert_eq!(step.value.float().unwrap().exact_signed_value(), Float::parse("0.1", &F64_PARAMS).unwrap().exact_signed_value().map(|v| -v));
+    let zero = eval("-0", &up, &BTreeMap::new()).unwrap();
+    assert!(zero.value.float().unwrap().sign());
+  }
+
+  #[test]
+  fn formats_reals() {
+    assert_eq!(format_real(&ratio(-1, 8)), "-.125");
+    assert_eq!(format_real(&ratio(1, 3)), "≈ 3.33333333333333333333333333333e-01");
+    // far too long to expand, in either direction
+    assert_eq!(format_real(&pow2(-1000)), "≈ 9.33263618503218878990089544724e-302");
+    assert_eq!(format_real(&pow2(1000)), "≈ 1.07150860718626732094842504906e+301");
+  }
+
+  #[test]
+  fn variables_and_errors() {
+    let vars = BTreeMap::from([("a".to_owned(), Float::parse("2", &F32_PARAMS).unwrap())]);
+    let step = eval("a * a", &FloatingPointEnv::default(), &vars).unwrap();
+    assert_eq!(step.value.float().unwrap().exact_signed_value(), Some(ratio(4, 1)));
+    assert!(matches!(eval("b", &FloatingPointEnv::default(), &vars), Err(EvalError::UnknownVariable(_))));
+    assert!(matches!(eval("foo(1)", &FloatingPointEnv::default(), &vars), Err(EvalError::UnknownOp(_))));
+    assert!(matches!(eval("add(1)", &FloatingPointEnv::default(), &vars), Err(EvalError::WrongArgCount(..))));
+    assert!(matches!(eval("add(f32, 1)", &FloatingPointEnv::default(), &vars), Err(EvalError::MisplacedType)));
+  }
+}
@@ -0,0 +1,408 @@
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::vec::IntoIter;
+use thiserror::Error;
+use crate::floats::{FloatParameters, FloatParseError};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+  Number(String),
+  Ident(String),
+  Symbol(char),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BinaryOp {
+  Add,
+  Sub,
+  Mul,
+  Div,
+}
+
+impl BinaryOp {
+  /// The name of the operation that implements this operator.
+  pub fn op_name(self) -> &'static str {
+    match self {
+      BinaryOp::Add => "add",
+      BinaryOp::Sub => "sub",
+      BinaryOp::Mul => "mul",
+      BinaryOp::Div => "div",
+    }
+  }
+
+  pub fn symbol(self) -> char {
+    match self {
+      BinaryOp::Add => '+',
+      BinaryOp::Sub => '-',
+      BinaryOp::Mul => '*',
+      BinaryOp::Div => '/',
+    }
+  }
+
+  fn precedence(self) -> u8 {
+    match self {
+      BinaryOp::Add | BinaryOp::Sub => 1,
+      BinaryOp::Mul | BinaryOp::Div => 2,
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+  /// A literal without a type, which is parsed in the default format.
+  Literal(String),
+  /// A literal with an explicit type, e.g. `f32 0.1`.
+  Typed(FloatParameters, String),
+  /// A type on its own, e.g. the `bf16` in `convert(a, bf16)`.
+  Type(FloatParameters),
+  Var(String),
+  Neg(Box<Expr>),
+  Binary(BinaryOp, Box<Expr>, Box<Expr>),
+  Call(String, Vec<Expr>),
+}
+
+impl Display for Expr {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    // parenthesize operands that would otherwise be parsed differently
+    fn operand(f: &mut Formatter<'_>, e: &Expr, precedence: u8, right: bool) -> std::fmt::Result {
+      match e {
+        Expr::Binary(op, ..) if op.precedence() < precedence || (right && op.precedence() == precedence) => write!(f, "({})", e),
+        _ => write!(f, "{}", e),
+      }
+    }
+    match self {
+      Expr::Literal(s) | Expr::Var(s) => write!(f, "{}", s),
+      Expr::Typed(ty, s) => write!(f, "{} {}", ty, s),
+      Expr::Type(ty) => write!(f, "{}", ty),
+      Expr::Neg(e) => {
+        write!(f, "-")?;
+        operand(f, e, u8::MAX, false)
+      }
+      Expr::Binary(op, lhs, rhs) => {
+        operand(f, lhs, op.precedence(), false)?;
+        write!(f, " {} ", op.symbol())?;
+        operand(f, rhs, op.precedence(), true)
+      }
+      Expr::Call(name, args) => {
+        write!(f, "{}(", name)?;
+        for (i, arg) in args.iter().enumerate() {
+          if i != 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{}", arg)?;
+        }
+        write!(f, ")")
+      }
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+  Assign(String, Expr),
+  Expr(Expr),
+}
+
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum ExprError {
+  #[error("Unexpected character '{1}' at position {0}")]
+  UnexpectedChar(usize, char),
+  #[error("Unexpected \"{1}\" at position {0}")]
+  UnexpectedToken(usize, String),
+  #[error("Invalid type at position {0}: {1}")]
+  InvalidType(usize, FloatParseError),
+  #[error("Unexpected end of expression")]
+  UnexpectedEnd,
+}
+
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+  let chars = s.char_indices().collect::<Vec<_>>();
+  let mut tokens = Vec::new();
+  let mut i = 0;
+  let take_while = |mut i: usize, pred: &dyn Fn(char) -> bool| {
+    while i < chars.len() && pred(chars[i].1) {
+      i += 1;
+    }
+    i
+  };
+  let slice = |from: usize, to: usize| chars[from..to].iter().map(|v| v.1).collect::<String>();
+  while i < chars.len() {
+    let (pos, c) = chars[i];
+    let next = chars.get(i + 1).map(|v| v.1);
+    if c.is_whitespace() {
+      i += 1;
+    } else if c == '0' && matches!(next, Some('x' | 'X' | 'b' | 'B')) {
+      // hex or binary literal
+      let end = take_while(i + 2, &|c| c.is_ascii_alphanumeric());
+      tokens.push((pos, Token::Number(slice(i, end))));
+      i = end;
+    } else if c.is_ascii_digit() || (c == '.' && next.is_some_and(|v| v.is_ascii_digit())) {
+      let mut end = take_while(i, &|c| c.is_ascii_digit() || c == '.');
+      if matches!(chars.get(end), Some((_, 'e' | 'E'))) {
+        let digits_start = if matches!(chars.get(end + 1), Some((_, '+' | '-'))) { end + 2 } else { end + 1 };
+        if chars.get(digits_start).is_some_and(|v| v.1.is_ascii_digit()) {
+          end = take_while(digits_start, &|c| c.is_ascii_digit());
+        }
+      }
+      tokens.push((pos, Token::Number(slice(i, end))));
+      i = end;
+    } else if c.is_alphabetic() || c == '_' {
+      let end = take_while(i, &|c| c.is_alphanumeric() || c == '_');
+      tokens.push((pos, Token::Ident(slice(i, end))));
+      i = end;
+    } else if "+-*/(),=".contains(c) {
+      tokens.push((pos, Token::Symbol(c)));
+      i += 1;
+    } else {
+      return Err(ExprError::UnexpectedChar(pos, c));
+    }
+  }
+  Ok(tokens)
+}
+
+struct Parser {
+  tokens: Peekable<IntoIter<(usize, Token)>>,
+}
+
+impl Parser {
+  fn peek(&mut self) -> Option<&Token> {
+    self.tokens.peek().map(|v| &v.1)
+  }
+
+  fn peek_symbol(&mut self) -> Option<char> {
+    match self.peek() {
+      Some(Token::Symbol(c)) => Some(*c),
+      _ => None,
+    }
+  }
+
+  fn unexpected(&mut self) -> ExprError {
+    match self.tokens.next() {
+      Some((pos, Token::Number(v) | Token::Ident(v))) => ExprError::UnexpectedToken(pos, v),
+      Some((pos, Token::Symbol(c))) => ExprError::UnexpectedToken(pos, c.to_string()),
+      None => ExprError::UnexpectedEnd,
+    }
+  }
+
+  fn expect_symbol(&mut self, c: char) -> Result<(), ExprError> {
+    if self.peek_symbol() == Some(c) {
+      self.tokens.next();
+      Ok(())
+    } else {
+      Err(self.unexpected())
+    }
+  }
+
+  fn expect_end(&mut self) -> Result<(), ExprError> {
+    if self.peek().is_some() {
+      Err(self.unexpected())
+    } else {
+      Ok(())
+    }
+  }
+
+  fn expr(&mut self) -> Result<Expr, ExprError> {
+    let mut lhs = self.term()?;
+    loop {
+      let op = match self.peek_symbol() {
+        Some('+') => BinaryOp::Add,
+        Some('-') => BinaryOp::Sub,
+        _ => return Ok(lhs),
+      };
+      self.tokens.next();
+      lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+    }
+  }
+
+  fn term(&mut self) -> Result<Expr, ExprError> {
+    let mut lhs = self.unary()?;
+    loop {
+      let op = match self.peek_symbol() {
+        Some('*') => BinaryOp::Mul,
+        Some('/') => BinaryOp::Div,
+        _ => return Ok(lhs),
+      };
+      self.tokens.next();
+      lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+    }
+  }
+
+  fn unary(&mut self) -> Result<Expr, ExprError> {
+    match self.peek_symbol() {
+      Some('-') => {
+        self.tokens.next();
+        // the literal is rounded before it is negated, as in C, so the rounding mode sees its magnitude
+        Ok(Expr::Neg(Box::new(self.unary()?)))
+      }
+      Some('+') => {
+        self.tokens.next();
+        self.unary()
+      }
+      _ => self.primary(),
+    }
+  }
+
+  fn primary(&mut self) -> Result<Expr, ExprError> {
+    match self.tokens.next() {
+      Some((_, Token::Number(v))) => Ok(Expr::Literal(v)),
+      Some((_, Token::Symbol('('))) => {
+        let e = self.expr()?;
+        self.expect_symbol(')')?;
+        Ok(e)
+      }
+      Some((pos, Token::Ident(name))) => {
+        if let Some(ty) = self.type_after(pos, &name)? {
+          return self.typed_literal(ty);
+        }
+        if self.peek_symbol() == Some('(') {
+          self.tokens.next();
+          let mut args = Vec::new();
+          if self.peek_symbol() != Some(')') {
+            args.push(self.expr()?);
+            while self.peek_symbol() == Some(',') {
+              self.tokens.next();
+              args.push(self.expr()?);
+            }
+          }
+          self.expect_symbol(')')?;
+          return Ok(Expr::Call(name, args));
+        }
+        if matches!(name.to_ascii_lowercase().as_str(), "inf" | "infinity" | "nan") {
+          return Ok(Expr::Literal(name));
+        }
+        Ok(Expr::Var(name))
+      }
+      Some((pos, Token::Symbol(c))) => Err(ExprError::UnexpectedToken(pos, c.to_string())),
+      None => Err(ExprError::UnexpectedEnd),
+    }
+  }
+
+  /// Parses the rest of a type if `name` starts one.
+  fn type_after(&mut self, pos: usize, name: &str) -> Result<Option<FloatParameters>, ExprError> {
+    if name == "custom" && self.peek_symbol() == Some('(') {
+      let mut s = String::from("custom(");
+      self.tokens.next();
+      loop {
+        match self.tokens.next() {
+          Some((_, Token::Symbol(')'))) => break,
+          Some((_, Token::Number(v) | Token::Ident(v))) => s.push_str(&v),
+          Some((_, Token::Symbol(c))) => s.push(c),
+          None => return Err(ExprError::UnexpectedEnd),
+        }
+      }
+      s.push(')');
+      return FloatParameters::parse(&s).map(Some).map_err(|e| ExprError::InvalidType(pos, e));
+    }
+    Ok(FloatParameters::parse(name).ok())
+  }
+
+  /// Parses the literal following a type, if there is one.
+  fn typed_literal(&mut self, ty: FloatParameters) -> Result<Expr, ExprError> {
+    let sign = match self.peek_symbol() {
+      Some(c @ ('-' | '+')) => {
+        self.tokens.next();
+        Some(c)
+      }
+      _ => None,
+    };
+    let literal = match self.peek() {
+      Some(Token::Number(v)) => v.clone(),
+      Some(Token::Ident(v)) if matches!(v.to_ascii_lowercase().as_str(), "inf" | "infinity" | "nan") => v.clone(),
+      _ if sign.is_some() => return Err(self.unexpected()),
+      _ => return Ok(Expr::Type(ty)),
+    };
+    self.tokens.next();
+    Ok(Expr::Typed(ty, sign.map(String::from).unwrap_or_default() + &literal))
+  }
+}
+
+/// Parses an arithmetic expression such as `(0.1 + 0.2) * f32 3`.
+pub fn parse_expr(s: &str) -> Result<Expr, ExprError> {
+  let mut parser = Parser { tokens: tokenize(s)?.into_iter().peekable() };
+  let e = parser.expr()?;
+  parser.expect_end()?;
+  Ok(e)
+}
+
+/// Parses either an assignment `name = expr` or an expression.
+pub fn parse_statement(s: &str) -> Result<Statement, ExprError> {
+  let tokens = tokenize(s)?;
+  if let [(_, Token::Ident(name)), (_, Token::Symbol('=')), ..] = tokens.as_slice() {
+    let name = name.clone();
+    let mut parser = Parser { tokens: tokens.into_iter().skip(2).collect::<Vec<_>>().into_iter().peekable() };
+    let e = parser.expr()?;
+    parser.expect_end()?;
+    return Ok(Statement::Assign(name, e));
+  }
+  let mut parser = Parser { tokens: tokens.into_iter().peekable() };
+  let e = parser.expr()?;
+  parser.expect_end()?;
+  Ok(Statement::Expr(e))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::floats::{FloatParseError, BF16_PARAMS, F32_PARAMS};
+
+  fn parse(s: &str) -> Expr {
+    match parse_statement(s).unwrap() {
+      Statement::Expr(e) => e,
+      Statement::Assign(..) => panic!("{} parsed as an assignment", s),
+    }
+  }
+
+  fn lit(s: &str) -> Box<Expr> {
+    Box::new(Expr::Literal(s.to_owned()))
+  }
+
+  fn var(s: &str) -> Box<Expr> {
+    Box::new(Expr::Var(s.to_owned()))
+  }
+
+  #[test]
+  fn precedence_and_associativity() {
+    assert_eq!(parse("1 + 2 * 3"), Expr::Binary(BinaryOp::Add, lit("1"), Box::new(Expr::Binary(BinaryOp::Mul, lit("2"), lit("3")))));
+    assert_eq!(parse("a - b - c"), Expr::Binary(BinaryOp::Sub, Box::new(Expr::Binary(BinaryOp::Sub, var("a"), var("b"))), var("c")));
+    assert_eq!(parse("(1 + 2) / x"), Expr::Binary(BinaryOp::Div, Box::new(Expr::Binary(BinaryOp::Add, lit("1"), lit("2"))), var("x")));
+  }
+
+  #[test]
+  fn literals_and_types() {
+    assert_eq!(parse("1.5e-3"), Expr::Literal("1.5e-3".into()));
+    assert_eq!(parse("0x3f800000"), Expr::Literal("0x3f800000".into()));
+    assert_eq!(parse("f32 -0.1"), Expr::Typed(F32_PARAMS, "-0.1".into()));
+    assert_eq!(parse("custom(5, 2) inf"), Expr::Typed(FloatParameters::new(5, 2), "inf".into()));
+    assert_eq!(parse("NaN"), Expr::Literal("NaN".into()));
+  }
+
+  #[test]
+  fn calls_and_assignments() {
+    assert_eq!(parse("convert(a, bf16)"), Expr::Call("convert".into(), vec![Expr::Var("a".into()), Expr::Type(BF16_PARAMS)]));
+    assert_eq!(parse_statement("b = -a").unwrap(), Statement::Assign("b".into(), Expr::Neg(var("a"))));
+  }
+
+  #[test]
+  fn signs() {
+    assert_eq!(parse("-0"), Expr::Neg(lit("0")));
+    assert_eq!(parse("--1"), Expr::Neg(Box::new(Expr::Neg(lit("1")))));
+    assert_eq!(parse("-(0.1)"), Expr::Neg(lit("0.1")));
+    assert_eq!(parse("+x"), Expr::Var("x".into()));
+  }
+
+  #[test]
+  fn display_round_trips() {
+    for s in ["(a + b) * c", "a - (b - c)", "a - b - c", "-(a + 1)", "convert(a * 2, bf16)", "f32 -0.1 / 3"] {
+      assert_eq!(parse_expr(s).unwrap().to_string(), s);
+    }
+    assert_eq!(parse_expr("((a)) * (b * c)").unwrap().to_string(), "a * (b * c)");
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(parse_statement("1 +"), Err(ExprError::UnexpectedEnd));
+    assert_eq!(parse_statement("1 $ 2"), Err(ExprError::UnexpectedChar(2, '$')));
+    assert_eq!(parse_statement("(1 2)"), Err(ExprError::UnexpectedToken(3, "2".into())));
+    assert_eq!(parse_statement("custom(x) 1"), Err(ExprError::InvalidType(0, FloatParseError::InvalidType("custom(x)".into()))));
+  }
+}
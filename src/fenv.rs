@@ -1,8 +1,19 @@
+use std::cmp::Ordering;
+use std::fmt::Write;
+use std::str::FromStr;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use crate::floats::{BitVec, Float, FloatParameters};
+use crate::ops::Exception;
+use crate::str_conv::format_binary_point;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum RoundingMode {
   /// Rounds to the nearest float, if two floats are
   /// equally far away, then use the one with even LSB
+  #[default]
   TiesToEven,
   /// Rounds to the nearest float, if two floats are
   /// equally far away, then use the one with larger magnitude.
@@ -15,9 +26,234 @@ pub enum RoundingMode {
   TowardZero
 }
 
+impl RoundingMode {
+  /// Decides whether a magnitude that was truncated to a whole number of ULPs should be rounded up.
+  /// `odd` is whether the truncated magnitude has an odd LSB, and `vs_half` compares the discarded
+  /// part to half an ULP.
+  pub fn rounds_up(self, sign: bool, odd: bool, vs_half: Ordering, inexact: bool) -> bool {
+    match self {
+      RoundingMode::TiesToEven => vs_half == Ordering::Greater || (vs_half == Ordering::Equal && odd),
+      RoundingMode::TiesToAway => vs_half != Ordering::Less,
+      RoundingMode::TowardPositive => inexact && !sign,
+      RoundingMode::TowardNegative => inexact && sign,
+      RoundingMode::TowardZero => false,
+    }
+  }
+
+  /// Whether an overflowing result becomes infinity, rather than the largest finite number.
+  pub fn overflows_to_inf(self, sign: bool) -> bool {
+    match self {
+      RoundingMode::TiesToEven | RoundingMode::TiesToAway => true,
+      RoundingMode::TowardPositive => !sign,
+      RoundingMode::TowardNegative => sign,
+      RoundingMode::TowardZero => false,
+    }
+  }
+}
+
+impl FromStr for RoundingMode {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let normalized = s.to_ascii_lowercase().replace(['-', '_'], "");
+    match normalized.as_str() {
+      "tiestoeven" | "nearesteven" | "rne" => Ok(RoundingMode::TiesToEven),
+      "tiestoaway" | "nearestaway" | "rna" => Ok(RoundingMode::TiesToAway),
+      "towardpositive" | "up" | "rtp" => Ok(RoundingMode::TowardPositive),
+      "towardnegative" | "down" | "rtn" => Ok(RoundingMode::TowardNegative),
+      "towardzero" | "rtz" => Ok(RoundingMode::TowardZero),
+      _ => Err(format!("Unknown rounding mode \"{}\"", s)),
+    }
+  }
+}
+
 /// Represents settings that the environment uses when executing floating point operations.
 /// These include rounding mode, flushing subnormals to zero, etc.
+#[derive(Clone, Debug, Default)]
 pub struct FloatingPointEnv {
   pub rounding_mode: RoundingMode,
   pub flush_subnormals_to_zero: bool,
-}
\ No newline at end of file
+}
+
+impl FloatingPointEnv {
+  /// Rounds the exact value `(-1)^sign * value` to a float of `output_type`,
+  /// explaining each step. `value` must not be negative.
+  pub fn round(&self, f: &mut dyn Write, sign: bool, value: &BigRational, output_type: &FloatParameters) -> Result<(Float, Exception), std::fmt::Error> {
+    writeln!(f, "- The current rounding mode is: {:?}", self.rounding_mode)?;
+    if value.is_zero() {
+      writeln!(f, "- The exact result is zero")?;
+      return Ok((Float::from_fields(output_type, sign, 0, &BigUint::zero()), Exception::default()));
+    }
+    let num = value.numer().magnitude();
+    let den = value.denom().magnitude();
+    let p = output_type.sig_bits as i64;
+
+    // floor(log2(value)) is either the difference in bit lengths or one less
+    let mut exp = num.bits() as i64 - den.bits() as i64;
+    let at_least_pow2 = if exp >= 0 { num >= &(den << exp) } else { &(num << -exp) >= den };
+    if !at_least_pow2 {
+      exp -= 1;
+    }
+    let mut q_exp = if exp < output_type.min_exp() {
+      writeln!(f, "- The exponent of the exact result is {}, but the minimum possible exponent is {}, encoding as subnormal", exp, output_type.min_exp())?;
+      output_type.min_exp()
+    } else {
+      writeln!(f, "- The exponent of the exact result is {}", exp)?;
+      exp
+    };
+
+    // the significand as an integer, in units of the ULP
+    let shift = q_exp - p;
+    let (sig_num, sig_den) = if shift >= 0 { (num.clone(), den << shift) } else { (num << -shift, den.clone()) };
+    let (mut q_sig, rem) = sig_num.div_rem(&sig_den);
+    let inexact = !rem.is_zero();
+    let vs_half = (rem << 1u8).cmp(&sig_den);
+    let rem_desc = match (inexact, vs_half) {
+      (false, _) => "nothing",
+      (true, Ordering::Less) => "less than half an ULP",
+      (true, Ordering::Equal) => "exactly half an ULP",
+      (true, Ordering::Greater) => "more than half an ULP",
+    };
+    writeln!(f, "- The significand is {}, with {} left over", format_binary_point(&q_sig, p as usize), rem_desc)?;
+
+    let mut exception = Exception::default();
+    if inexact {
+      exception = exception | Exception::INEXACT;
+      if self.rounding_mode.rounds_up(sign, q_sig.is_odd(), vs_half, inexact) {
+        q_sig += 1u32;
+        writeln!(f, "- Rounding the magnitude up to {}", format_binary_point(&q_sig, p as usize))?;
+        if q_sig.bits() as i64 > p + 1 {
+          q_sig >>= 1;
+          q_exp += 1;
+          writeln!(f, "- Rounding carried out of the significand, the exponent is now {}", q_exp)?;
+        }
+      } else {
+        writeln!(f, "- Rounding the magnitude down, discarding the left over bits")?;
+      }
+    }
+
+    if q_exp > output_type.max_exp() {
+      return self.overflow(f, sign, output_type);
+    }
+
+    let subnormal = (q_sig.bits() as i64) <= p;
+    if exp < output_type.min_exp() && inexact {
+      exception = exception | Exception::UNDERFLOW;
+    }
+    if subnormal && !q_sig.is_zero() && self.flush_subnormals_to_zero {
+      writeln!(f, "- The output is subnormal, flushing it to zero")?;
+      exception = exception | Exception::UNDERFLOW | Exception::INEXACT;
+      return Ok((Float::from_fields(output_type, sign, 0, &BigUint::zero()), exception));
+    }
+
+    let sig_field = q_sig & ((BigUint::one() << p) - 1u32);
+    if subnormal {
+      writeln!(f, "- Encoding as subnormal with raw exponent 0")?;
+      Ok((Float::from_fields(output_type, sign, 0, &sig_field), exception))
+    } else {
+      writeln!(f, "- Encoding as normal with raw exponent {}", q_exp + output_type.exp_bias as i64)?;
+      let mut sig_bits = BitVec::from_vec(sig_field.to_u32_digits());
+      sig_bits.resize(output_type.sig_bits, false);
+      Ok((Float::from_parts(output_type, sign, q_exp, &sig_bits), exception))
+    }
+  }
+
+  /// Rounds a value above the largest finite number of `output_type`, by its sign alone.
+  pub fn overflow(&self, f: &mut dyn Write, sign: bool, output_type: &FloatParameters) -> Result<(Float, Exception), std::fmt::Error> {
+    let exception = Exception::OVERFLOW | Exception::INEXACT;
+    if self.rounding_mode.overflows_to_inf(sign) {
+      writeln!(f, "- The output is too large, wrapping to infinity")?;
+      Ok((Float::inf(output_type, sign), exception))
+    } else {
+      writeln!(f, "- The output is too large, but this rounding mode gives the largest finite number")?;
+      Ok((Float::max_finite(output_type, sign), exception))
+    }
+  }
+
+  /// Rounds a nonzero value below half the smallest subnormal of `output_type`, by its sign alone.
+  /// It becomes zero or the smallest subnormal, depending on the rounding mode.
+  pub fn underflow(&self, f: &mut dyn Write, sign: bool, output_type: &FloatParameters) -> Result<(Float, Exception), std::fmt::Error> {
+    let exception = Exception::UNDERFLOW | Exception::INEXACT;
+    if self.rounding_mode.rounds_up(sign, false, Ordering::Less, true) && !self.flush_subnormals_to_zero {
+      writeln!(f, "- The output is less than half the smallest subnormal, rounding up to it")?;
+      Ok((Float::from_fields(output_type, sign, 0, &BigUint::one()), exception))
+    } else {
+      writeln!(f, "- The output is less than half the smallest subnormal, rounding down to zero")?;
+      Ok((Float::from_fields(output_type, sign, 0, &BigUint::zero()), exception))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bitvec::field::BitField;
+  use num_bigint::BigInt;
+  use super::*;
+  use crate::floats::{F32_PARAMS, F64_PARAMS};
+
+  fn round(mode: RoundingMode, sign: bool, value: BigRational) -> (u64, Exception) {
+    let env = FloatingPointEnv { rounding_mode: mode, ..Default::default() };
+    let (v, exception) = env.round(&mut String::new(), sign, &value, &F32_PARAMS).unwrap();
+    let encoding = (v.sign() as u64) << 31 | v.exponent_bits_integer() << 23 | v.significand_bits().load_le::<u64>();
+    (encoding, exception)
+  }
+
+  fn ratio(n: i64, d: i64) -> BigRational {
+    BigRational::new(BigInt::from(n), BigInt::from(d))
+  }
+
+  #[test]
+  fn exact_values_are_not_rounded() {
+    assert_eq!(round(RoundingMode::TiesToEven, false, ratio(3, 2)), (0x3fc00000, Exception::default()));
+    assert_eq!(round(RoundingMode::TiesToEven, true, ratio(3, 2)), (0xbfc00000, Exception::default()));
+    assert_eq!(round(RoundingMode::TowardZero, true, BigRational::zero()), (0x80000000, Exception::default()));
+  }
+
+  #[test]
+  fn ties() {
+    // 1 + 2^-24 is halfway between 1 and the next float
+    let half = ratio((1 << 24) + 1, 1 << 24);
+    assert_eq!(round(RoundingMode::TiesToEven, false, half.clone()), (0x3f800000, Exception::INEXACT));
+    assert_eq!(round(RoundingMode::TiesToAway, false, half.clone()), (0x3f800001, Exception::INEXACT));
+    // 1 + 3 × 2^-24 is halfway between two floats, the upper one is even
+    let half = ratio((1 << 24) + 3, 1 << 24);
+    assert_eq!(round(RoundingMode::TiesToEven, false, half), (0x3f800002, Exception::INEXACT));
+  }
+
+  #[test]
+  fn directed_rounding() {
+    let third = ratio(1, 3);
+    let (down, _) = round(RoundingMode::TowardZero, false, third.clone());
+    assert_eq!(round(RoundingMode::TowardPositive, false, third.clone()).0, down + 1);
+    assert_eq!(round(RoundingMode::TowardNegative, false, third.clone()).0, down);
+    assert_eq!(round(RoundingMode::TowardNegative, true, third.clone()).0, (down + 1) | 0x80000000);
+    assert_eq!(round(RoundingMode::TowardPositive, true, third).0, down | 0x80000000);
+  }
+
+  #[test]
+  fn overflow() {
+    let huge = BigRational::from_integer(BigInt::from(1) << 200);
+    assert_eq!(round(RoundingMode::TiesToEven, false, huge.clone()), (0x7f800000, Exception::OVERFLOW | Exception::INEXACT));
+    assert_eq!(round(RoundingMode::TowardZero, false, huge.clone()), (0x7f7fffff, Exception::OVERFLOW | Exception::INEXACT));
+    assert_eq!(round(RoundingMode::TowardPositive, true, huge).0, 0xff7fffff);
+  }
+
+  #[test]
+  fn subnormals_and_underflow() {
+    let tiny = |n: i64| BigRational::from_integer(BigInt::from(n)) / BigRational::from_integer(BigInt::from(1) << 149);
+    assert_eq!(round(RoundingMode::TiesToEven, false, tiny(3)), (3, Exception::default()));
+    // half of the smallest subnormal ties to zero
+    let (bits, exception) = round(RoundingMode::TiesToEven, false, tiny(1) / BigInt::from(2));
+    assert_eq!(bits, 0);
+    assert!(exception.contains(Exception::UNDERFLOW | Exception::INEXACT));
+    assert_eq!(round(RoundingMode::TowardPositive, false, tiny(1) / BigInt::from(2)).0, 1);
+  }
+
+  #[test]
+  fn flush_subnormals_to_zero() {
+    let env = FloatingPointEnv { flush_subnormals_to_zero: true, ..Default::default() };
+    let tiny = BigRational::from_integer(BigInt::from(3)) / BigRational::from_integer(BigInt::from(1) << 1074);
+    let (v, _) = env.round(&mut String::new(), false, &tiny, &F64_PARAMS).unwrap();
+    assert!(v.classify().zero());
+  }
+}
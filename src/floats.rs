@@ -1,9 +1,13 @@
-use std::iter::repeat;
-use bitvec::bitvec;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
 use bitvec::field::BitField;
-use bitvec::macros::internal::funty::Floating;
 use bitvec::order::Lsb0;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_rational::BigRational;
+use num_traits::Zero;
 use thiserror::Error;
+use crate::fenv::FloatingPointEnv;
+use crate::str_conv::{parse_decimal, DecimalLiteral};
 
 pub type BitVec = bitvec::vec::BitVec<u32, Lsb0>;
 pub type BitSlice = bitvec::slice::BitSlice<u32, Lsb0>;
@@ -30,28 +34,75 @@ pub const F64_PARAMS: FloatParameters = FloatParameters {
   pmin: 17
 };
 
+pub const F16_PARAMS: FloatParameters = FloatParameters {
+  exp_bits: 5,
+  exp_bias: 15,
+  sig_bits: 10,
+  pmin: 5
+};
+
+pub const BF16_PARAMS: FloatParameters = FloatParameters {
+  exp_bits: 8,
+  exp_bias: 127,
+  sig_bits: 7,
+  pmin: 4
+};
+
+/// The widest exponent field, which leaves room to add and subtract a few exponents as i64.
+const MAX_EXP_BITS: usize = 60;
+/// The widest significand field, which keeps the bit strings printable.
+const MAX_SIG_BITS: usize = 4096;
+
 impl FloatParameters {
-  pub fn parse(s: &str) -> Option<Self> {
-    if s == "double" || s == "f64" {
-      Some(F64_PARAMS)
+  /// Creates an IEEE-style format with the given number of exponent and (stored) significand bits.
+  pub fn new(exp_bits: usize, sig_bits: usize) -> Self {
+    // digits needed so that every float survives a round trip through decimal
+    let pmin = ((sig_bits + 1) as f64 * 2f64.log10()).ceil() as usize + 1;
+    FloatParameters {
+      exp_bits,
+      exp_bias: 1u64.checked_shl(exp_bits.saturating_sub(1) as u32).unwrap_or(0).saturating_sub(1),
+      sig_bits,
+      pmin,
+    }
+  }
+
+  pub fn parse(s: &str) -> Result<Self, FloatParseError> {
+    let s = s.trim();
+    let params = if s == "double" || s == "f64" {
+      F64_PARAMS
     } else if s == "float" || s == "f32" {
-      Some(F32_PARAMS)
+      F32_PARAMS
+    } else if s == "half" || s == "f16" {
+      F16_PARAMS
+    } else if s == "bfloat16" || s == "bf16" {
+      BF16_PARAMS
+    } else if let Some(args) = s.strip_prefix("custom").and_then(|v| v.trim_start().strip_prefix('(')) {
+      let invalid = || FloatParseError::InvalidType(s.to_owned());
+      let (exp_bits, sig_bits) = args.strip_suffix(')').and_then(|v| v.split_once(',')).ok_or_else(invalid)?;
+      let exp_bits = exp_bits.trim().parse().map_err(|_| invalid())?;
+      let sig_bits = sig_bits.trim().parse().map_err(|_| invalid())?;
+      FloatParameters::new(exp_bits, sig_bits)
     } else {
-      None
-    }
+      return Err(FloatParseError::InvalidType(s.to_owned()));
+    };
+    params.validate()?;
+    Ok(params)
   }
 
-  pub fn validate(&self) {
-    if self.exp_bits == 0 {
-      panic!("Exp bits must be greater than 0");
-    } else if self.exp_bits >= 64 {
-      panic!("Exp bits must be smaller than 64");
+  pub fn validate(&self) -> Result<(), FloatParseError> {
+    let invalid = |s: &str| Err(FloatParseError::InvalidParameters(s.to_owned()));
+    if self.exp_bits < 2 {
+      invalid("Exp bits must be at least 2")
+    } else if self.exp_bits > MAX_EXP_BITS {
+      invalid(&format!("Exp bits must be at most {}", MAX_EXP_BITS))
     } else if self.exp_bias >= (1 << self.exp_bits) {
-      panic!("Exp bias is too large");
+      invalid("Exp bias is too large")
     } else if self.sig_bits == 0 {
-      panic!("Sig bits must be larger than 0");
-    } else if self.sig_bits >= (1 << 63) {
-      panic!("Sig bits must be smaller than 2^63");
+      invalid("Sig bits must be larger than 0")
+    } else if self.sig_bits > MAX_SIG_BITS {
+      invalid(&format!("Sig bits must be at most {}", MAX_SIG_BITS))
+    } else {
+      Ok(())
     }
   }
 
@@ -68,6 +119,22 @@ impl FloatParameters {
   }
 }
 
+impl Display for FloatParameters {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    if self == &F64_PARAMS {
+      write!(f, "f64")
+    } else if self == &F32_PARAMS {
+      write!(f, "f32")
+    } else if self == &F16_PARAMS {
+      write!(f, "f16")
+    } else if self == &BF16_PARAMS {
+      write!(f, "bf16")
+    } else {
+      write!(f, "custom({}, {})", self.exp_bits, self.sig_bits)
+    }
+  }
+}
+
 #[derive(Clone, Debug)]
 pub struct Float {
   params: FloatParameters,
@@ -100,6 +167,50 @@ impl Float {
     }
   }
 
+  /// Returns the finite float with the largest magnitude.
+  pub fn max_finite(params: &FloatParameters, sign: bool) -> Self {
+    let exp = (1u64 << params.exp_bits) - 2;
+    let sig = (BigUint::from(1u32) << params.sig_bits) - 1u32;
+    Float::from_fields(params, sign, exp, &sig)
+  }
+
+  /// Creates a float from its encoding. `bits` must fit in the format.
+  pub fn from_bits(params: &FloatParameters, bits: &BigUint) -> Self {
+    let mut bits = BitVec::from_vec(bits.to_u32_digits());
+    debug_assert!(bits.last_one().is_none_or(|v| v < params.total_length()));
+    bits.resize(params.total_length(), false);
+    Float {
+      params: params.clone(),
+      bits,
+    }
+  }
+
+  /// Iterates over every encoding of a format in increasing order of the bits,
+  /// i.e. the positive numbers, the positive NaNs and then the same for the negative sign.
+  pub fn all_encodings(params: &FloatParameters) -> impl Iterator<Item = Float> + '_ {
+    let count = BigUint::from(1u32) << params.total_length();
+    std::iter::successors(Some(BigUint::from(0u32)), |v| Some(v + 1u32))
+      .take_while(move |v| v < &count)
+      .map(move |v| Float::from_bits(params, &v))
+  }
+
+  /// Creates a float from the raw contents of its fields.
+  /// `sig` must fit in the significand field, and `exp` in the exponent field.
+  pub fn from_fields(params: &FloatParameters, sign: bool, exp: u64, sig: &BigUint) -> Self {
+    let mut bits = BitVec::repeat(false, params.total_length());
+    let sig_digits = BitVec::from_vec(sig.to_u32_digits());
+    debug_assert!(sig_digits.last_one().is_none_or(|v| v < params.sig_bits));
+    let sig_len = sig_digits.len().min(params.sig_bits);
+    bits[0..sig_len].copy_from_bitslice(&sig_digits[0..sig_len]);
+    bits[params.sig_bits..params.sig_bits+params.exp_bits].store_le(exp);
+    let last = bits.len() - 1;
+    bits.set(last, sign);
+    Float {
+      params: params.clone(),
+      bits,
+    }
+  }
+
   pub fn from_parts(params: &FloatParameters, sign: bool, exp: i64, sig: &BitSlice) -> Self {
     assert_eq!(sig.len(), params.sig_bits);
     let mut bits = BitVec::repeat(false, params.total_length());
@@ -114,28 +225,29 @@ impl Float {
   }
   
   pub fn parse(s: &str, params: &FloatParameters) -> Result<Self, FloatParseError> {
-    params.validate();
-    let mut bits = if s.starts_with("0x") {
-      bitvec_from_hex(&s[2..], params.total_length())?
-    } else if s.starts_with("0b") {
-      bitvec_from_bitstr(&s[2..], params.total_length())?
-    } else if params == &F32_PARAMS {
-      //todo manual parsing
-      let f = s.parse::<f32>().map_err(|_| FloatParseError::InvalidDecimalLiteral)?;
-      let mut bits = BitVec::from_iter(repeat(false).take(32));
-      bits.store_le(f.to_bits());
-      bits
-    } else if params == &F64_PARAMS {
-      let f = s.parse::<f64>().map_err(|_| FloatParseError::InvalidDecimalLiteral)?;
-      let mut bits = BitVec::from_iter(repeat(false).take(64));
-      bits.store_le(f.to_bits());
-      bits
+    Float::parse_in_env(s, params, &FloatingPointEnv::default())
+  }
+
+  /// Parses a float, rounding decimal literals according to `env`.
+  pub fn parse_in_env(s: &str, params: &FloatParameters, env: &FloatingPointEnv) -> Result<Self, FloatParseError> {
+    params.validate()?;
+    let mut bits = if let Some(hex) = s.strip_prefix("0x") {
+      bitvec_from_hex(hex, params.total_length())?
+    } else if let Some(bin) = s.strip_prefix("0b") {
+      bitvec_from_bitstr(bin, params.total_length())?
     } else {
-      return Err(FloatParseError::NoParser);
+      let float = match parse_decimal(s).ok_or(FloatParseError::InvalidDecimalLiteral)? {
+        DecimalLiteral::Finite(sign, value) => env.round(&mut String::new(), sign, &value, params).unwrap().0,
+        DecimalLiteral::Inf(sign) => Float::inf(params, sign),
+        DecimalLiteral::NaN => Float::nan(params),
+      };
+      return Ok(float);
     };
-    while bits.len() < params.total_length() {
-      bits.push(false);
+    // leading zeros are allowed, and hex digits can hold a few bits more than the format
+    if bits[params.total_length().min(bits.len())..].any() {
+      return Err(FloatParseError::TooLong(bits.last_one().unwrap() + 1, params.total_length()));
     }
+    bits.resize(params.total_length(), false);
     Ok(Float {
       params: params.clone(),
       bits,
@@ -146,6 +258,17 @@ impl Float {
     *self.bits.last().unwrap()
   }
 
+  /// Sets the sign bit, leaving all other bits unchanged.
+  pub fn set_sign(&mut self, sign: bool) {
+    let last = self.bits.len() - 1;
+    self.bits.set(last, sign);
+  }
+
+  /// Returns all bits of the number, starting at the least significant bit of the significand.
+  pub fn bits(&self) -> &BitSlice {
+    &self.bits
+  }
+
   /// Returns the raw exponent bits of the number.
   pub fn exponent_bits(&self) -> &BitSlice {
     let s = self.params.sig_bits;
@@ -156,8 +279,7 @@ impl Float {
   /// Returns the exponent bits as an integer.
   pub fn exponent_bits_integer(&self) -> u64 {
     let exp = self.exponent_bits();
-    let exp_unbiased = exp.load_le::<u64>();
-    exp_unbiased
+    exp.load_le::<u64>()
   }
 
   /// Returns the logical exponent, i.e. subnormals have the same logical exponent as
@@ -187,6 +309,119 @@ impl Float {
     bits
   }
 
+  /// Returns the exponent of the leading 1 bit, i.e. floor(log2(|value|)).
+  /// Unlike [`Float::exponent_logical`], subnormals get the exponent they would have if normalized.
+  /// Panics if the float is zero or not finite.
+  pub fn normalized_exponent(&self) -> i64 {
+    let sig = bits_to_biguint(&self.significand_logical());
+    assert!(!sig.is_zero(), "Float is zero");
+    self.exponent_logical() + sig.bits() as i64 - 1 - self.params.sig_bits as i64
+  }
+
+  /// Returns the magnitude of the float as an exact fraction.
+  /// Returns `None` if the float is not finite.
+  pub fn exact_value(&self) -> Option<BigRational> {
+    if !self.classify().finite() {
+      return None;
+    }
+    let sig = BigInt::from(bits_to_biguint(&self.significand_logical()));
+    let exp = self.exponent_logical() - self.params.sig_bits as i64;
+    Some(BigRational::from_integer(sig) * pow2(exp))
+  }
+
+  /// Returns the value of the float as an exact fraction, including its sign.
+  /// Returns `None` if the float is not finite.
+  pub fn exact_signed_value(&self) -> Option<BigRational> {
+    self.exact_value().map(|v| if self.sign() { -v } else { v })
+  }
+
+  /// Maps the sign-magnitude encoding to an integer that orders the same way as the floats:
+  /// negative floats become the negated magnitude, so -0 and +0 both map to 0.
+  /// NaNs order outside the infinities, on the side of their sign.
+  pub fn ordered_integer(&self) -> BigInt {
+    let magnitude = BigInt::from(bits_to_biguint(&self.bits[..self.bits.len() - 1]));
+    if self.sign() { -magnitude } else { magnitude }
+  }
+
+  /// Compares the values of two floats, possibly of different formats, as the IEEE 754 comparison predicates do.
+  /// Returns `None` if either is a NaN, and treats -0 and +0 as equal.
+  pub fn compare(&self, other: &Float) -> Option<Ordering> {
+    let (a, b) = (self.classify(), other.classify());
+    if a.nan() || b.nan() {
+      return None;
+    }
+    // infinities are compared by their sign, finite values by their exact value
+    let rank = |v: &Float, class: FloatClass| if class.inf() { if v.sign() { -1 } else { 1 } } else { 0 };
+    Some(rank(self, a).cmp(&rank(other, b)).then_with(|| match (self.exact_signed_value(), other.exact_signed_value()) {
+      (Some(x), Some(y)) => x.cmp(&y),
+      _ => Ordering::Equal,
+    }))
+  }
+
+  /// Orders two floats as the IEEE 754 totalOrder predicate: -NaN < -Inf < ... < -0 < +0 < ... < +Inf < +NaN.
+  /// Within a format NaNs are ordered by their payload. Across formats, NaNs of the same sign are equal.
+  pub fn total_cmp(&self, other: &Float) -> Ordering {
+    if self.params == other.params {
+      return self.ordered_integer().cmp(&other.ordered_integer()).then_with(|| other.sign().cmp(&self.sign()));
+    }
+    let rank = |v: &Float| if v.classify().nan() { if v.sign() { -1 } else { 1 } } else { 0 };
+    rank(self).cmp(&rank(other))
+      .then_with(|| self.compare(other).unwrap_or(Ordering::Equal))
+      .then_with(|| other.sign().cmp(&self.sign()))
+  }
+
+  /// The inverse of [`Float::ordered_integer`]. 0 maps to +0.
+  /// `value` must fit in the format, values beyond the infinities give NaNs.
+  pub fn from_ordered_integer(params: &FloatParameters, value: &BigInt) -> Self {
+    let mut bits = BitVec::from_vec(value.magnitude().to_u32_digits());
+    bits.resize(params.total_length() - 1, false);
+    bits.push(value.sign() == Sign::Minus);
+    Float {
+      params: params.clone(),
+      bits,
+    }
+  }
+
+  /// Returns the smallest float that compares greater than this one, as the IEEE 754 nextUp operation.
+  pub fn next_up(&self) -> Float {
+    match self.classify() {
+      FloatClass::QuietNaN | FloatClass::SignallingNaN | FloatClass::PositiveInf => self.clone(),
+      FloatClass::NegativeInf => Float::max_finite(&self.params, true),
+      _ => self.step_ordered(1),
+    }
+  }
+
+  /// Returns the largest float that compares less than this one, as the IEEE 754 nextDown operation.
+  pub fn next_down(&self) -> Float {
+    match self.classify() {
+      FloatClass::QuietNaN | FloatClass::SignallingNaN | FloatClass::NegativeInf => self.clone(),
+      FloatClass::PositiveInf => Float::max_finite(&self.params, false),
+      _ => self.step_ordered(-1),
+    }
+  }
+
+  /// Returns the next float after this one in the direction of `toward`, as C's `nextafter`.
+  /// Returns NaN if either is NaN, and this float with the sign of `toward` if they are equal.
+  pub fn next_after(&self, toward: &Float) -> Float {
+    match self.compare(toward) {
+      None => Float::nan(&self.params),
+      Some(Ordering::Less) => self.next_up(),
+      Some(Ordering::Greater) => self.next_down(),
+      // equal values only differ in the sign of a zero
+      Some(Ordering::Equal) if self.classify().zero() => Float::from_fields(&self.params, toward.sign(), 0, &BigUint::zero()),
+      Some(Ordering::Equal) => self.clone(),
+    }
+  }
+
+  fn step_ordered(&self, step: i32) -> Float {
+    let mut next = Float::from_ordered_integer(&self.params, &(self.ordered_integer() + step));
+    if next.classify().zero() {
+      // stepping towards zero from the smallest subnormal keeps the sign
+      next.set_sign(self.sign());
+    }
+    next
+  }
+
   pub fn params(&self) -> &FloatParameters {
     &self.params
   }
@@ -239,6 +474,20 @@ impl Float {
   }
 }
 
+/// Returns 2^exp as a fraction.
+pub fn pow2(exp: i64) -> BigRational {
+  if exp >= 0 {
+    BigRational::from_integer(BigInt::from(1) << exp)
+  } else {
+    BigRational::new(BigInt::from(1), BigInt::from(1) << -exp)
+  }
+}
+
+/// Converts a bit slice into an integer, with the first bit as the least significant.
+pub fn bits_to_biguint(bits: &BitSlice) -> BigUint {
+  BigUint::new(bits.chunks(32).map(|v| v.load_le::<u32>()).collect())
+}
+
 fn bitvec_from_hex(s: &str, len: usize) -> Result<BitVec, FloatParseError> {
   let mut vec = BitVec::with_capacity(len);
   s.as_bytes().iter().rev().try_for_each(|v| {
@@ -282,18 +531,20 @@ fn bitvec_from_bitstr(s: &str, len: usize) -> Result<BitVec, FloatParseError> {
   }).map(|()| vec)
 }
 
-#[derive(Error, Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Error, Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub enum FloatParseError {
   #[error("Invalid hex digit")]
   InvalidHexDigit,
   #[error("Invalid binary digit")]
   InvalidBinaryDigit,
-  #[error("Too many float bits")]
-  TooLong,
+  #[error("Too many float bits, the literal has {0} bits but the format only has {1}")]
+  TooLong(usize, usize),
   #[error("Invalid decimal literal")]
   InvalidDecimalLiteral,
-  #[error("Must specify hex or binary for non-standard float format")]
-  NoParser
+  #[error("Unknown type \"{0}\", expected float, double, half, bfloat16 (or f32, f64, f16, bf16), or custom(<exponent>, <significand>)")]
+  InvalidType(String),
+  #[error("Invalid float parameters: {0}")]
+  InvalidParameters(String),
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -335,4 +586,127 @@ impl FloatClass {
   pub fn negative(self) -> bool {
     matches!(self, FloatClass::NegativeInf | FloatClass::NegativeNormal | FloatClass::NegativeSubnormal | FloatClass::NegativeZero)
   }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn f32(s: &str) -> Float {
+    Float::parse(s, &F32_PARAMS).unwrap()
+  }
+
+  #[test]
+  fn parse_types() {
+    assert_eq!(FloatParameters::parse("double"), Ok(F64_PARAMS));
+    assert_eq!(FloatParameters::parse(" bf16 "), Ok(BF16_PARAMS));
+    assert_eq!(FloatParameters::parse("custom( 5 , 10 )"), Ok(F16_PARAMS));
+    assert_eq!(FloatParameters::parse("f33"), Err(FloatParseError::InvalidType("f33".into())));
+    assert_eq!(FloatParameters::parse("custom(5)"), Err(FloatParseError::InvalidType("custom(5)".into())));
+    assert_eq!(FloatParameters::parse("custom(5, -1)"), Err(FloatParseError::InvalidType("custom(5, -1)".into())));
+  }
+
+  #[test]
+  fn invalid_parameters() {
+    assert!(matches!(FloatParameters::parse("custom(1, 3)"), Err(FloatParseError::InvalidParameters(_))));
+    assert!(matches!(FloatParameters::parse("custom(64, 3)"), Err(FloatParseError::InvalidParameters(_))));
+    assert!(matches!(FloatParameters::parse("custom(63, 3)"), Err(FloatParseError::InvalidParameters(_))));
+    assert!(FloatParameters::parse("custom(60, 3)").is_ok());
+    assert!(matches!(FloatParameters::parse("custom(8, 100000000000)"), Err(FloatParseError::InvalidParameters(_))));
+    assert!(FloatParameters::parse("custom(15, 4096)").is_ok());
+    assert!(matches!(FloatParameters::parse("custom(5, 0)"), Err(FloatParseError::InvalidParameters(_))));
+  }
+
+  #[test]
+  fn parse_literals() {
+    assert_eq!(f32("0x3f800000").exact_signed_value(), f32("1").exact_signed_value());
+    assert_eq!(f32("0b1").exact_signed_value(), f32("0x00000001").exact_signed_value());
+    assert_eq!(Float::parse("0x1ff", &F32_PARAMS).unwrap().significand_bits().count_ones(), 9);
+    // leading zeros don't count toward the width
+    assert_eq!(f32("0x00003f800000").exact_signed_value(), f32("1").exact_signed_value());
+    assert_eq!(f32(&format!("0b{}1", "0".repeat(40))).exact_signed_value(), f32("0x00000001").exact_signed_value());
+    assert!(f32("-inf").classify().inf());
+  }
+
+  #[test]
+  fn parse_errors() {
+    assert_eq!(Float::parse("0x1ffffffff", &F32_PARAMS).unwrap_err(), FloatParseError::TooLong(33, 32));
+    // the digits fit, but the value doesn't
+    assert_eq!(Float::parse("0xff", &FloatParameters::new(2, 4)).unwrap_err(), FloatParseError::TooLong(8, 7));
+    assert_eq!(Float::parse(&format!("0b{}", "1".repeat(33)), &F32_PARAMS).unwrap_err(), FloatParseError::TooLong(33, 32));
+    assert_eq!(Float::parse("0xfg", &F32_PARAMS).unwrap_err(), FloatParseError::InvalidHexDigit);
+    assert_eq!(Float::parse("0b102", &F32_PARAMS).unwrap_err(), FloatParseError::InvalidBinaryDigit);
+    assert_eq!(Float::parse("1.2.3", &F32_PARAMS).unwrap_err(), FloatParseError::InvalidDecimalLiteral);
+  }
+
+  #[test]
+  fn compare() {
+    assert_eq!(f32("1").compare(&f32("2")), Some(Ordering::Less));
+    assert_eq!(f32("-0").compare(&f32("0")), Some(Ordering::Equal));
+    assert_eq!(f32("-inf").compare(&f32("-3e38")), Some(Ordering::Less));
+    assert_eq!(f32("nan").compare(&f32("nan")), None);
+    assert_eq!(f32("1").compare(&f32("nan")), None);
+    // values are compared exactly across formats
+    let tenth = Float::parse("0.1", &F64_PARAMS).unwrap();
+    assert_eq!(f32("0.1").compare(&tenth), Some(Ordering::Greater));
+    assert_eq!(f32("0.5").compare(&Float::parse("0.5", &F64_PARAMS).unwrap()), Some(Ordering::Equal));
+  }
+
+  #[test]
+  fn total_cmp() {
+    // the default NaN has all bits set, so it is negative
+    let ordered = [
+      Float::nan(&F32_PARAMS), f32("0xffc00000"), f32("-inf"), f32("-1"), f32("0x80000001"), f32("-0"),
+      f32("0"), f32("0x00000001"), f32("1"), f32("inf"), f32("0x7fc00000"), f32("0x7fffffff"),
+    ];
+    for (i, a) in ordered.iter().enumerate() {
+      for (j, b) in ordered.iter().enumerate() {
+        assert_eq!(a.total_cmp(b), i.cmp(&j), "{} vs {}", i, j);
+      }
+    }
+  }
+
+  #[test]
+  fn total_cmp_across_formats() {
+    let f64 = |s: &str| Float::parse(s, &F64_PARAMS).unwrap();
+    assert_eq!(f32("-0").total_cmp(&f64("0")), Ordering::Less);
+    assert_eq!(f32("0").total_cmp(&f64("-0")), Ordering::Greater);
+    assert_eq!(f32("0.1").total_cmp(&f64("0.1")), Ordering::Greater);
+    assert_eq!(f32("0x7fc00000").total_cmp(&f64("inf")), Ordering::Greater);
+    assert_eq!(f32("nan").total_cmp(&f64("-inf")), Ordering::Less);
+    assert_eq!(f32("nan").total_cmp(&f64("nan")), Ordering::Equal);
+  }
+
+  fn bits(f: &Float) -> u64 {
+    f.bits().load_le::<u64>()
+  }
+
+  #[test]
+  fn next_up_and_down() {
+    assert_eq!(bits(&f32("1").next_up()), 0x3f800001);
+    assert_eq!(bits(&f32("1").next_down()), 0x3f7fffff);
+    assert_eq!(bits(&f32("-1").next_up()), 0xbf7fffff);
+    // crossing zero, in both directions
+    assert_eq!(bits(&f32("0").next_up()), 0x00000001);
+    assert_eq!(bits(&f32("-0").next_up()), 0x00000001);
+    assert_eq!(bits(&f32("0").next_down()), 0x80000001);
+    assert_eq!(bits(&f32("0x00000001").next_down()), 0x00000000);
+    assert_eq!(bits(&f32("0x80000001").next_up()), 0x80000000);
+    // subnormal to normal
+    assert_eq!(bits(&f32("0x007fffff").next_up()), 0x00800000);
+    // the infinities
+    assert_eq!(bits(&f32("0x7f7fffff").next_up()), 0x7f800000);
+    assert_eq!(bits(&f32("inf").next_up()), 0x7f800000);
+    assert_eq!(bits(&f32("inf").next_down()), 0x7f7fffff);
+    assert_eq!(bits(&f32("-inf").next_up()), 0xff7fffff);
+    assert_eq!(bits(&f32("-inf").next_down()), 0xff800000);
+    assert!(f32("nan").next_up().classify().nan());
+  }
+
+  #[test]
+  fn next_after() {
+    assert_eq!(bits(&f32("1").next_after(&f32("2"))), 0x3f800001);
+    assert_eq!(bits(&f32("1").next_after(&f32("-inf"))), 0x3f7fffff);
+    assert_eq!(bits(&f32("0").next_after(&f32("-0"))), 0x80000000);
+    assert!(f32("1").next_after(&f32("nan")).classify().nan());
+  }
+}
@@ -0,0 +1,45 @@
+use num_bigint::BigUint;
+use num_rational::BigRational;
+use crate::eval::format_real;
+use crate::floats::{pow2, Float, FloatParameters};
+use crate::printers::{BOLD, DARK_GRAY, RESET};
+
+fn print_value(name: &str, exact: &str, value: &BigRational) {
+  println!("{}{:<25}{}{:<24} {}{}{}", BOLD, name, RESET, exact, DARK_GRAY, format_real(value), RESET);
+}
+
+/// Prints the limits and precision of a float format.
+pub fn print_format_info(params: &FloatParameters) {
+  let p = params.sig_bits as i64 + 1;
+  let max_finite = Float::max_finite(params, false).exact_value().unwrap();
+  let min_normal = pow2(params.min_exp());
+  let min_subnormal = pow2(params.min_exp() - params.sig_bits as i64);
+
+  println!("{}{}{}: 1 sign bit, {} exponent bits (bias {}), {} significand bits ({} with the implicit bit)",
+    BOLD, params, RESET, params.exp_bits, params.exp_bias, params.sig_bits, p);
+  println!("Exponent range: {} to {}", params.min_exp(), params.max_exp());
+  println!();
+  print_value("Max finite", &format!("(2 - 2^{}) × 2^{}", 1 - p, params.max_exp()), &max_finite);
+  print_value("Min normal", &format!("2^{}", params.min_exp()), &min_normal);
+  print_value("Min subnormal", &format!("2^{}", params.min_exp() - params.sig_bits as i64), &min_subnormal);
+  print_value("Machine epsilon", &format!("2^{}", 1 - p), &pow2(1 - p));
+  print_value("Unit roundoff", &format!("2^{}", -p), &pow2(-p));
+  println!();
+
+  // decimal strings with this many digits survive decimal -> float -> decimal,
+  // and floats survive float -> decimal -> float when printed with pmin digits
+  let decimal_digits = ((p - 1) as f64 * 2f64.log10()).floor() as usize;
+  println!("{}{:<25}{}{}", BOLD, "Digits decimal→float", RESET, decimal_digits);
+  println!("{}{:<25}{}{}", BOLD, "Digits float→decimal", RESET, params.pmin);
+
+  // every exponent except the one for inf and NaN, both signs
+  let finite_count = BigUint::from((1u64 << params.exp_bits) - 1) << (params.sig_bits + 1);
+  println!("{}{:<25}{}{} (including +0 and -0)", BOLD, "Finite values", RESET, finite_count);
+
+  // all integers up to 2^p are exact, unless the format overflows before that
+  if p <= params.max_exp() {
+    print_value("Largest consecutive int", &format!("2^{}", p), &pow2(p));
+  } else {
+    print_value("Largest consecutive int", "floor(max finite)", &BigRational::from_integer(max_finite.to_integer()));
+  }
+}
@@ -0,0 +1,162 @@
+use std::fmt::{Display, Formatter};
+use num_bigint::{BigInt, BigUint};
+use num_traits::{One, Zero};
+use thiserror::Error;
+
+/// A fixed-width two's complement integer type such as `i32` or `u64`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IntType {
+  pub bits: usize,
+  pub signed: bool,
+}
+
+pub const I32_TYPE: IntType = IntType { bits: 32, signed: true };
+
+/// The widest integer type that can be parsed, which keeps the bit strings printable.
+const MAX_INT_BITS: usize = 4096;
+
+impl IntType {
+  /// Parses `i<bits>` or `u<bits>`, e.g. `i32` or `u7`.
+  pub fn parse(s: &str) -> Result<Self, IntParseError> {
+    let s = s.trim();
+    let invalid = || IntParseError::InvalidType(s.to_owned());
+    let signed = match s.chars().next() {
+      Some('i') => true,
+      Some('u') => false,
+      _ => return Err(invalid()),
+    };
+    let bits = s[1..].parse::<usize>().map_err(|_| invalid())?;
+    if bits == 0 || bits > MAX_INT_BITS {
+      return Err(IntParseError::InvalidWidth(bits));
+    }
+    Ok(IntType { bits, signed })
+  }
+
+  pub fn min(&self) -> BigInt {
+    if self.signed {
+      -(BigInt::one() << (self.bits - 1))
+    } else {
+      BigInt::zero()
+    }
+  }
+
+  pub fn max(&self) -> BigInt {
+    let magnitude_bits = if self.signed { self.bits - 1 } else { self.bits };
+    (BigInt::one() << magnitude_bits) - 1
+  }
+
+  pub fn contains(&self, value: &BigInt) -> bool {
+    value >= &self.min() && value <= &self.max()
+  }
+
+  /// Keeps the low `bits` bits of `value`, as a two's complement cast between integer types does.
+  pub fn wrap(&self, value: &BigInt) -> BigInt {
+    let encoded = BigInt::from(self.encode(value));
+    if self.signed && encoded > self.max() {
+      encoded - (BigInt::one() << self.bits)
+    } else {
+      encoded
+    }
+  }
+
+  /// Returns the two's complement encoding of the low `bits` bits of `value`.
+  pub fn encode(&self, value: &BigInt) -> BigUint {
+    let modulus = BigInt::one() << self.bits;
+    (((value % &modulus) + &modulus) % &modulus).magnitude().clone()
+  }
+}
+
+impl Display for IntType {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+  }
+}
+
+/// An integer along with the type it is stored in.
+#[derive(Clone, Debug)]
+pub struct Integer {
+  pub value: BigInt,
+  pub ty: IntType,
+}
+
+impl Integer {
+  /// Parses a decimal integer, or a hex or binary encoding (prefixed with 0x or 0b) of a value of `ty`.
+  /// Encodings are two's complement for signed types, so `0xff` is -1 as an `i8`.
+  pub fn parse(s: &str, ty: &IntType) -> Result<Self, IntParseError> {
+    let s = s.trim().replace('_', "");
+    let encoded = if let Some(hex) = s.strip_prefix("0x") {
+      Some(BigUint::parse_bytes(hex.as_bytes(), 16).ok_or(IntParseError::InvalidLiteral)?)
+    } else if let Some(bin) = s.strip_prefix("0b") {
+      Some(BigUint::parse_bytes(bin.as_bytes(), 2).ok_or(IntParseError::InvalidLiteral)?)
+    } else {
+      None
+    };
+    let value = match encoded {
+      Some(v) if v.bits() as usize > ty.bits => return Err(IntParseError::TooLong(v.bits() as usize, *ty)),
+      Some(v) => ty.wrap(&v.into()),
+      None => s.parse::<BigInt>().map_err(|_| IntParseError::InvalidLiteral)?,
+    };
+    if !ty.contains(&value) {
+      return Err(IntParseError::OutOfRange(*ty));
+    }
+    Ok(Integer { value, ty: *ty })
+  }
+
+  /// The bits of the encoding, most significant first.
+  pub fn bit_string(&self) -> String {
+    format!("{:0width$b}", self.ty.encode(&self.value), width = self.ty.bits)
+  }
+}
+
+#[derive(Error, Debug)]
+pub enum IntParseError {
+  #[error("Unknown integer type \"{0}\", expected i<bits> or u<bits>, e.g. i32 or u64")]
+  InvalidType(String),
+  #[error("Integer types must have between 1 and {max} bits, got {0}", max = MAX_INT_BITS)]
+  InvalidWidth(usize),
+  #[error("Invalid integer literal")]
+  InvalidLiteral,
+  #[error("Too many bits, the literal has {0} bits but {1} only has {bits}", bits = .1.bits)]
+  TooLong(usize, IntType),
+  #[error("The value is out of range for {0}")]
+  OutOfRange(IntType),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(s: &str, ty: &str) -> Result<i64, IntParseError> {
+    Integer::parse(s, &IntType::parse(ty).unwrap()).map(|v| v.value.try_into().unwrap())
+  }
+
+  #[test]
+  fn parse_types() {
+    assert_eq!(IntType::parse("i32").unwrap(), I32_TYPE);
+    assert_eq!(IntType::parse("u7").unwrap(), IntType { bits: 7, signed: false });
+    assert!(matches!(IntType::parse("f32"), Err(IntParseError::InvalidType(_))));
+    assert!(matches!(IntType::parse("i0"), Err(IntParseError::InvalidWidth(0))));
+    assert!(matches!(IntType::parse("u5000"), Err(IntParseError::InvalidWidth(5000))));
+  }
+
+  #[test]
+  fn parse_literals() {
+    assert_eq!(parse("-128", "i8").unwrap(), -128);
+    assert_eq!(parse("1_000", "u16").unwrap(), 1000);
+    assert_eq!(parse("0xff", "i8").unwrap(), -1);
+    assert_eq!(parse("0xff", "u8").unwrap(), 255);
+    assert_eq!(parse("0b1000", "i4").unwrap(), -8);
+    assert!(matches!(parse("128", "i8"), Err(IntParseError::OutOfRange(_))));
+    assert!(matches!(parse("-1", "u8"), Err(IntParseError::OutOfRange(_))));
+    assert!(matches!(parse("0x1ff", "u8"), Err(IntParseError::TooLong(9, _))));
+    assert!(matches!(parse("1.5", "i32"), Err(IntParseError::InvalidLiteral)));
+  }
+
+  #[test]
+  fn wrap_and_encode() {
+    let i8_type = IntType::parse("i8").unwrap();
+    assert_eq!(i8_type.wrap(&BigInt::from(300)), BigInt::from(44));
+    assert_eq!(i8_type.wrap(&BigInt::from(-129)), BigInt::from(127));
+    assert_eq!(Integer { value: BigInt::from(-2), ty: i8_type }.bit_string(), "11111110");
+  }
+}
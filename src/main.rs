@@ -1,25 +1,53 @@
-use std::collections::BTreeMap;
-use std::fmt::Write;
-use std::rc::Rc;
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
 use clap::{Parser, Subcommand};
+use thiserror::Error;
+use crate::batch::{run_batch, BatchFormat};
+use crate::compare::print_comparison;
+use crate::diff::print_diff;
+use crate::eval::{format_real, print_tree, Evaluator};
+use crate::expr::parse_expr;
+use crate::info::print_format_info;
 use crate::fenv::{FloatingPointEnv, RoundingMode};
-use crate::floats::{Float, FloatParameters, F64_PARAMS};
-use crate::ops::collect_ops;
-use crate::printers::{collect_printers, Printer, RED, RESET};
+use crate::floats::{Float, FloatParameters, FloatParseError, F64_PARAMS};
+use crate::ints::{IntParseError, IntType, Integer};
+use crate::ops::{collect_ops, Exception, Kind, Value};
+use crate::printers::{collect_printers, print_error, print_float, print_value, PrinterOptions, select_printers, set_color_choice, ColorChoice, BOLD, RESET};
+use crate::repl::Repl;
+use crate::table::{print_format_table, MAX_TABLE_BITS};
 
 mod floats;
+mod ints;
 mod ops;
 mod printers;
 mod str_conv;
 mod fenv;
+mod expr;
+mod eval;
+mod repl;
+mod batch;
+mod info;
+mod table;
+mod compare;
+mod diff;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 #[command(propagate_version = false)]
 pub struct Cli {
   /// Which representations the float should be printed in.
-  #[arg(short, long, default_values = ["binary", "exact", "ulp"])]
+  /// Defaults to binary, exact and ulp, and for `show` also literal.
+  #[arg(short, long)]
   show: Vec<String>,
+  /// When to use colored output. `auto` disables colors on stdout and stderr separately when they aren't terminals, and everywhere if `NO_COLOR` is set.
+  #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+  color: ColorChoice,
+  /// The number of significant digits used by decimal printers such as `sci`.
+  /// Defaults to enough digits to round-trip the type.
+  #[arg(short, long, value_parser = clap::value_parser!(u16).range(1..))]
+  digits: Option<u16>,
   #[command(subcommand)]
   command: Commands
 }
@@ -40,101 +68,387 @@ pub enum Commands {
   Op {
     /// The operation to perform
     op: String,
-    /// Arguments, alternative type and value
-    args: Vec<String>
+    /// Arguments, alternative type and value. Integer operands take an integer type such as i32 or u64.
+    /// Settings follow the operands, e.g. the integer type and semantics of `to-int f64 1e10 u16 java`.
+    /// The semantics for out-of-range and NaN inputs are rust and arm (saturate), c (undefined behaviour),
+    /// x86 (the "integer indefinite" value), wasm (trap) and java. Java casts never trap: they saturate
+    /// to int or long, and narrower types keep the low bits of that.
+    #[arg(allow_hyphen_values = true)]
+    args: Vec<String>,
+    /// The type of the result. Defaults to the type of the first float argument, or f64.
+    /// Must come before the operation, everything after it is read as arguments so that they can be negative.
+    #[arg(short, long)]
+    to: Option<String>,
   },
+  /// Compares two floats, e.g. an expected and an actual result, and reports how far apart they are.
+  Compare {
+    /// Either `TYPE A B`, or `TYPE_A A TYPE_B B` to compare values of different types.
+    #[arg(num_args = 3..=4, required = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+  },
+  /// Shows which bits of two floats of the same type differ, and what each difference does to the value.
+  Diff {
+    /// The type of both floats, see `show` for the syntax.
+    #[arg(id = "TYPE")]
+    type_: String,
+    #[arg(allow_hyphen_values = true)]
+    a: String,
+    #[arg(allow_hyphen_values = true)]
+    b: String,
+  },
+  /// Prints consecutive floats between two bounds, including the bounds.
+  Range {
+    /// The type of float, see `show` for the syntax.
+    #[arg(id = "TYPE")]
+    type_: String,
+    /// The first value, rounded to the type.
+    #[arg(allow_hyphen_values = true)]
+    from: String,
+    /// The last value, rounded to the type. Values are walked downwards if it is below `from`.
+    #[arg(allow_hyphen_values = true)]
+    to: String,
+    /// Only print every Nth float.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    step: u64,
+    /// Stop after printing this many floats.
+    #[arg(short, long, default_value_t = 1000)]
+    limit: usize,
+  },
+  /// Evaluates an arithmetic expression, rounding after every operation.
+  Eval {
+    /// The type of literals in the expression, see `show` for the syntax.
+    /// Literals can also be given a type explicitly, e.g. `f32 0.1`.
+    #[arg(id = "TYPE")]
+    type_: String,
+    /// The expression, e.g. "(0.1 + 0.2) * 3 - 0.9". Supports + - * /, parentheses and operations such as convert(x, bf16).
+    #[arg(required = true, allow_hyphen_values = true)]
+    expr: Vec<String>,
+    /// The rounding mode used by every operation.
+    #[arg(short, long, default_value = "TiesToEven")]
+    rounding: RoundingMode,
+  },
+  /// Prints every value in a file, or stdin, one per line.
+  /// Each line is either a value, or a type followed by a value.
+  Batch {
+    /// The type of lines that only contain a value.
+    #[arg(short, long = "type", id = "TYPE")]
+    type_: Option<String>,
+    /// The output format.
+    #[arg(short, long, value_enum, default_value_t = BatchFormat::Table)]
+    format: BatchFormat,
+    /// The file to read. Reads stdin if not given or `-`.
+    file: Option<PathBuf>,
+  },
+  /// Prints the limits and precision of a float type.
+  FormatInfo {
+    /// The type of float, see `show` for the syntax.
+    #[arg(id = "TYPE")]
+    type_: String,
+  },
+  /// Lists every encoding of a small float type, grouped by exponent.
+  Table {
+    /// The type of float, see `show` for the syntax. At most 12 bits wide.
+    #[arg(id = "TYPE")]
+    type_: String,
+    /// Also draw a chart of the value against the encoding.
+    #[arg(short, long)]
+    plot: bool,
+  },
+  /// Starts an interactive session where floats can be bound to variables and operated on.
+  Repl {},
   /// List all supported printers that can be used with the --show (-s) flag.
   Printers {},
   /// List all supported operations.
   Operations {}
 }
 
-fn print_using_printer(printer: &dyn Printer, val: &Float) {
-  let strs = printer.print(&val);
-  let pname = printer.name();
-  println!("{}: {}", pname, &strs[0]);
-  strs.iter().skip(1).for_each(|v| {
-    println!("{}  {}", " ".repeat(pname.len()), v);
-  });
+/// An invalid command line argument.
+#[derive(Error, Debug)]
+#[error("Invalid {name} \"{value}\": {source}")]
+pub struct ArgError {
+  name: String,
+  value: String,
+  source: ParseError,
 }
 
-fn print_float(value: &Float, show: &[String], printers: &BTreeMap<String, Rc<dyn Printer>>) {
-  let printers = show.iter().filter_map(|v| {
-    let p = printers.get(v).cloned();
-    if p.is_none() {
-      eprintln!("{}Unknown printer: {}{}", RED, v, RESET);
-    }
-    p
-  }).collect::<Vec<_>>();
+#[derive(Error, Debug)]
+pub enum ParseError {
+  #[error(transparent)]
+  Float(#[from] FloatParseError),
+  #[error(transparent)]
+  Int(#[from] IntParseError),
+}
 
-  printers.iter().for_each(|v| print_using_printer(v.as_ref(), &value));
+fn parse_type_arg(name: &str, value: &str) -> Result<FloatParameters, ArgError> {
+  FloatParameters::parse(value).map_err(|source| ArgError { name: name.to_owned(), value: value.to_owned(), source: source.into() })
+}
+
+fn parse_float_arg(name: &str, value: &str, ftype: &FloatParameters) -> Result<Float, ArgError> {
+  Float::parse(value, ftype).map_err(|source| ArgError { name: name.to_owned(), value: value.to_owned(), source: source.into() })
+}
+
+fn parse_int_type_arg(name: &str, value: &str) -> Result<IntType, ArgError> {
+  IntType::parse(value).map_err(|source| ArgError { name: name.to_owned(), value: value.to_owned(), source: source.into() })
+}
+
+fn parse_int_arg(name: &str, value: &str, ty: &IntType) -> Result<Integer, ArgError> {
+  Integer::parse(value, ty).map_err(|source| ArgError { name: name.to_owned(), value: value.to_owned(), source: source.into() })
+}
+
+fn report(e: ArgError) {
+  print_error(e);
 }
 
 fn main() {
   let args = Cli::parse();
-  let printers = collect_printers();
+  set_color_choice(args.color);
+  let literal = match &args.command {
+    Commands::Show { value, .. } => Some(value.clone()),
+    _ => None,
+  };
+  let printers = collect_printers(&PrinterOptions { digits: args.digits.map(usize::from), literal });
+  let mut show = args.show;
+  if show.is_empty() {
+    show = ["binary", "exact", "ulp"].map(String::from).to_vec();
+    if matches!(args.command, Commands::Show { .. }) {
+      show.push("literal".to_owned());
+    }
+  }
   let ops = collect_ops();
   match args.command {
     Commands::Show { type_, value } => {
-      let ftype = FloatParameters::parse(&type_);
-      let Some(ftype) = ftype else {
-        eprintln!("{}Error parsing type \"{}\"{}", RED, type_, RESET);
-        return;
-      };
-      let fvalue_v = Float::parse(&value, &ftype);
-      let Ok(fvalue) = fvalue_v else {
-        eprintln!("{}Error parsing float \"{}\": {}{}", RED, value, fvalue_v.unwrap_err(), RESET);
-        return;
+      let fvalue = match parse_type_arg("type", &type_).and_then(|v| parse_float_arg("value", &value, &v)) {
+        Ok(fvalue) => fvalue,
+        Err(e) => return report(e),
       };
 
-      print_float(&fvalue, &args.show, &printers);
+      print_float(&fvalue, &show, &printers);
     }
     Commands::Printers { .. } => {
       printers.iter().for_each(|(k, v)| {
         println!("{}: {}", k, v.description());
       })
     }
-    Commands::Op { op, args: args2 } => {
+    Commands::Op { op, args: args2, to } => {
       let box_op = ops.get(&op);
       let Some(box_op) = box_op else {
-        eprintln!("{}Unknown operation: {}{}", RED, op, RESET);
+        print_error(format_args!("Unknown operation: {}", op));
         return;
       };
 
-      if args2.len() != box_op.num_params() * 2 {
-        eprintln!("{}Wrong number of arguments, expected {}{}", RED, box_op.num_params(), RESET);
+      let operand_args = box_op.num_params() * 2;
+      if args2.len() < operand_args || args2.len() > operand_args + box_op.settings().len() {
+        print_error(format_args!("Wrong number of arguments, expected {} operands", box_op.num_params()));
         return;
       }
-      let params = args2.chunks(2).map(|v| {
-        let ty = FloatParameters::parse(&v[0]).unwrap();
-        let float = Float::parse(&v[1], &ty).unwrap();
-        float
-      }).collect::<Vec<_>>();
-
+      let (args2, settings) = args2.split_at(operand_args);
+      let box_op = if settings.is_empty() {
+        box_op.clone()
+      } else {
+        match box_op.with_settings(settings) {
+          Ok(op) => op,
+          Err(e) => {
+            print_error(format_args!("Invalid setting for {}: {}", op, e));
+            return;
+          }
+        }
+      };
       let letters = "ABCDEFG";
-      params.iter().zip(letters.chars()).for_each(|(float, name)| {
-        println!("\x1b[1mInput {}\x1b[0m", name);
-        print_float(&float, &args.show, &printers);
+      let params = args2.chunks(2).zip(box_op.param_kinds()).zip(letters.chars()).map(|((v, kind), name)| match kind {
+        Kind::Float => {
+          let ty = parse_type_arg(&format!("type of input {}", name), &v[0])?;
+          parse_float_arg(&format!("value of input {}", name), &v[1], &ty).map(Value::Float)
+        }
+        Kind::Int => {
+          let ty = parse_int_type_arg(&format!("integer type of input {}", name), &v[0])?;
+          parse_int_arg(&format!("value of input {}", name), &v[1], &ty).map(Value::Int)
+        }
+      }).collect::<Result<Vec<_>, _>>();
+      let params = match params {
+        Ok(params) => params,
+        Err(e) => return report(e),
+      };
+      let output_type = match to.map(|v| parse_type_arg("output type", &v)) {
+        Some(Ok(ty)) => ty,
+        Some(Err(e)) => return report(e),
+        None => params.iter().find_map(|v| v.float()).map_or(F64_PARAMS, |v| v.params().clone()),
+      };
+
+      params.iter().zip(letters.chars()).for_each(|(value, name)| {
+        println!("{}Input {}{}", BOLD, name, RESET);
+        print_value(value, &show, &printers);
         println!();
       });
 
       println!("---");
       let mut s = String::new();
-      let (ret, exp) = box_op.execute_visual(&mut s, &FloatingPointEnv {
-        rounding_mode: RoundingMode::TiesToEven,
-        flush_subnormals_to_zero: false,
-      }, &params, &F64_PARAMS).unwrap();
+      let (ret, exception) = box_op.execute_visual(&mut s, &FloatingPointEnv::default(), &params, &output_type).unwrap();
       println!("{}", s);
 
       println!("---");
       println!();
 
-      println!("\x1b[1mResult\x1b[0m");
-      print_float(&ret, &args.show, &printers);
+      println!("{}Result{}", BOLD, RESET);
+      print_value(&ret, &show, &printers);
+      if exception != Exception::default() {
+        println!("Exceptions: {}", exception);
+      }
+    }
+    Commands::Compare { args: args2 } => {
+      let (type_a, a, type_b, b) = match args2.as_slice() {
+        [ty, a, b] => (ty, a, ty, b),
+        [type_a, a, type_b, b] => (type_a, a, type_b, b),
+        _ => unreachable!(),
+      };
+      let parse = |ty: &str, v: &str, name: &str| {
+        let ty = parse_type_arg(&format!("type of {}", name), ty)?;
+        parse_float_arg(&format!("value of {}", name), v, &ty)
+      };
+      let (a, b) = match parse(type_a, a, "A").and_then(|a| Ok((a, parse(type_b, b, "B")?))) {
+        Ok(v) => v,
+        Err(e) => return report(e),
+      };
+      for (name, float) in [("A", &a), ("B", &b)] {
+        println!("{}{} ({}){}", BOLD, name, float.params(), RESET);
+        print_float(float, &show, &printers);
+        println!();
+      }
+      print_comparison(&a, &b);
+    }
+    Commands::Diff { type_, a, b } => {
+      let values = parse_type_arg("type", &type_).and_then(|ty| {
+        Ok((parse_float_arg("value of A", &a, &ty)?, parse_float_arg("value of B", &b, &ty)?))
+      });
+      match values {
+        Ok((a, b)) => print_diff(&a, &b),
+        Err(e) => report(e),
+      }
+    }
+    Commands::Range { type_, from, to, step, limit } => {
+      let bounds = parse_type_arg("type", &type_).and_then(|ty| {
+        Ok((parse_float_arg("start", &from, &ty)?, parse_float_arg("end", &to, &ty)?))
+      });
+      let (from, to) = match bounds {
+        Ok(bounds) => bounds,
+        Err(e) => return report(e),
+      };
+      let Some(direction) = from.compare(&to) else {
+        print_error("The bounds of a range can't be NaN");
+        return;
+      };
+      let up = direction != Ordering::Greater;
+      let mut current = from;
+      let mut count = 0;
+      loop {
+        if count == limit {
+          println!("Stopped after {} floats, use --limit to print more", limit);
+          break;
+        }
+        println!("{}#{}{}", BOLD, count, RESET);
+        print_float(&current, &show, &printers);
+        println!();
+        count += 1;
+        // every step must stay within the bounds, infinities end the walk
+        let mut next = current.clone();
+        for _ in 0..step {
+          next = if up { next.next_up() } else { next.next_down() };
+        }
+        let past_end = next.compare(&to) != Some(direction) && next.compare(&to) != Some(Ordering::Equal);
+        if past_end || next.bits() == current.bits() {
+          break;
+        }
+        current = next;
+      }
+    }
+    Commands::Eval { type_, expr, rounding } => {
+      let ftype = match parse_type_arg("type", &type_) {
+        Ok(ftype) => ftype,
+        Err(e) => return report(e),
+      };
+      let expr = match parse_expr(&expr.join(" ")) {
+        Ok(expr) => expr,
+        Err(e) => {
+          print_error(format_args!("Error parsing expression: {}", e));
+          return;
+        }
+      };
+      let env = FloatingPointEnv { rounding_mode: rounding, ..FloatingPointEnv::default() };
+      let evaluator = Evaluator {
+        ops: &ops,
+        env: &env,
+        vars: &Default::default(),
+        literal_type: &ftype,
+        output_type: None,
+        trace: false,
+      };
+      let step = match evaluator.eval(&expr) {
+        Ok(step) => step,
+        Err(e) => {
+          print_error(e);
+          return;
+        }
+      };
+
+      println!("Evaluating in {} with rounding mode {:?}", ftype, rounding);
+      println!();
+      print_tree(&step, "", "", "");
+      println!();
+      if let Some(real) = &step.real {
+        println!("{}Exact real result{}: {}", BOLD, RESET, format_real(real));
+      }
+      println!("{}Exceptions{}: {}", BOLD, RESET, step.all_exceptions());
+      println!();
+      println!("{}Result{}", BOLD, RESET);
+      print_value(&step.value, &show, &printers);
+    }
+    Commands::Batch { type_, format, file } => {
+      let default_type = match type_.map(|v| parse_type_arg("default type", &v)).transpose() {
+        Ok(default_type) => default_type,
+        Err(e) => return report(e),
+      };
+      if format == BatchFormat::Json {
+        // escape codes would end up inside the JSON strings
+        set_color_choice(ColorChoice::Never);
+      }
+      let printers = select_printers(&show, &printers);
+      match file.filter(|v| v.as_os_str() != "-") {
+        Some(path) => {
+          let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+              print_error(format_args!("Error opening {}: {}", path.display(), e));
+              return;
+            }
+          };
+          run_batch(BufReader::new(file), default_type.as_ref(), format, &printers);
+        }
+        None => run_batch(std::io::stdin().lock(), default_type.as_ref(), format, &printers),
+      }
+    }
+    Commands::FormatInfo { type_ } => {
+      match parse_type_arg("type", &type_) {
+        Ok(ftype) => print_format_info(&ftype),
+        Err(e) => report(e),
+      }
+    }
+    Commands::Table { type_, plot } => {
+      let ftype = match parse_type_arg("type", &type_) {
+        Ok(ftype) => ftype,
+        Err(e) => return report(e),
+      };
+      if ftype.total_length() > MAX_TABLE_BITS {
+        print_error(format_args!("{} has {} bits, tables are limited to {} bits", ftype, ftype.total_length(), MAX_TABLE_BITS));
+        return;
+      }
+      print_format_table(&ftype, plot);
+    }
+    Commands::Repl { .. } => {
+      Repl::new(printers, ops, show).run(std::io::stdin().lock());
     }
     Commands::Operations { .. } => {
       ops.iter().for_each(|(k, v)| {
-        println!("{}", k);
+        let settings = v.settings().iter().map(|v| format!(" [{}]", v)).collect::<String>();
+        println!("{}{}", k, settings);
       })
     }
   }
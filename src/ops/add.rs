@@ -1,12 +1,12 @@
 use std::cmp::{max, min};
 use std::fmt::{Write};
-use std::iter::repeat;
 use std::mem::swap;
 use num_bigint::{BigInt, Sign};
-use crate::fenv::FloatingPointEnv;
-use crate::floats::{BitVec, Float, FloatClass, FloatParameters};
-use crate::ops::{Exception, Op};
-use crate::printers::{bit2char, print_bitset, DARK_GRAY, PINK, RESET, YELLOW};
+use num_rational::BigRational;
+use crate::fenv::{FloatingPointEnv, RoundingMode};
+use crate::floats::{bits_to_biguint, pow2, BitVec, Float, FloatParameters};
+use crate::ops::{floats, float_result, flush_inputs, propagate_nan, Exception, Op, Value};
+use crate::printers::{bit2char, colors_enabled, print_bitset, DARK_GRAY, PINK, RESET, YELLOW};
 
 pub struct AddSub(pub bool);
 
@@ -15,26 +15,17 @@ impl Op for AddSub {
     2
   }
 
-  fn execute(&self, env: &FloatingPointEnv, params: &[Float], output_type: &FloatParameters) -> (Float, Exception) {
-    todo!()
-  }
-
-  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Float], output_type: &FloatParameters) -> Result<(Float, Exception), std::fmt::Error> {
-    let mut a = &params[0];
-    let mut b = &params[1];
-
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
     writeln!(f, "\n1. Classify inputs\n")?;
-    let a_class = params[0].classify();
-    let b_class = params[1].classify();
-    // check nans
-    if a_class.nan() {
-      writeln!(f, "- Input A is NaN, return NaN")?;
-      return Ok((Float::nan(&output_type), Exception::INVALID_OPERATION))
-    }
-    if b_class.nan() {
-      writeln!(f, "- Input B is NaN, return NaN")?;
-      return Ok((Float::nan(&output_type), Exception::INVALID_OPERATION))
+    let params = flush_inputs(f, env, params)?;
+    if let Some(ret) = propagate_nan(f, &params, output_type)? {
+      return Ok(ret);
     }
+    let mut a = &params[0];
+    let mut b = &params[1];
+    let a_class = a.classify();
+    let b_class = b.classify();
 
     // check infs
     let a_inf = a_class.inf();
@@ -44,23 +35,24 @@ impl Op for AddSub {
       (true, true) => {
         if sub {
           writeln!(f, "- Operation simplifies to Infinity - Infinity, return NaN")?;
-          return Ok((Float::nan(&output_type), Exception::INVALID_OPERATION))
+          return Ok((Float::nan(output_type).into(), Exception::INVALID_OPERATION))
         }
         if a.sign() {
           writeln!(f, "- Operation simplifies to -(Infinity + Infinity), return -Infinity")?;
-          return Ok((Float::inf(&output_type, true), Exception::default()))
+          return Ok((Float::inf(output_type, true).into(), Exception::default()))
         } else {
           writeln!(f, "- Operation simplifies to Infinity + Infinity, return Infinity")?;
-          return Ok((Float::inf(&output_type, false), Exception::default()))
+          return Ok((Float::inf(output_type, false).into(), Exception::default()))
         }
       }
       (true, false) | (false, true) => {
-        if (a_inf && a.sign()) || (b_inf && b.sign()) {
+        // the sign of b is flipped when subtracting
+        if (a_inf && a.sign()) || (b_inf && (b.sign() ^ self.0)) {
           writeln!(f, "- Operation simplifies to -Infinity +/- Finite, return -Infinity")?;
-          return Ok((Float::inf(&output_type, true), Exception::default()))
+          return Ok((Float::inf(output_type, true).into(), Exception::default()))
         } else {
           writeln!(f, "- Operation simplifies to Infinity +/- Finite, return Infinity")?;
-          return Ok((Float::inf(&output_type, false), Exception::default()))
+          return Ok((Float::inf(output_type, false).into(), Exception::default()))
         }
       }
       _ => {}
@@ -82,77 +74,65 @@ impl Op for AddSub {
     let b_sig = b.significand_logical();
     let a_exp = a.exponent_logical();
     let b_exp = b.exponent_logical();
-    let left_digit = max(a_exp, b_exp); // = max_exp
-    let min_exp = min(a_exp, b_exp);
-    let right_digit = min(a_exp - a_sig.len() as i64, b_exp - b_sig.len() as i64);
+    // exponents of the least significant bit of each significand
+    let a_lsb = a_exp - (a_sig.len() as i64 - 1);
+    let b_lsb = b_exp - (b_sig.len() as i64 - 1);
+    let left_digit = max(a_exp, b_exp);
+    let right_digit = min(a_lsb, b_lsb);
     debug_assert!(left_digit - right_digit >= 0);
     let diff = (left_digit - right_digit) as usize;
 
     const EXTRA_PREPAD: usize = 2;
     const EXTRA_POSTPAD: usize = 1;
-    fn print_significand(f: &mut dyn Write, sig: &BitVec, prepad: usize, postpad: usize) -> std::fmt::Result {
-      write!(f, "{}{}{}{}", " ".repeat(prepad), PINK, bit2char(*sig.last().unwrap()), YELLOW)?;
-      print_bitset(f, &sig[0..sig.len() - 1])?;
-      write!(f, "{}{}...{}\n", DARK_GRAY, "0".repeat(postpad), RESET)?;
-      Ok(())
+    // each row starts with a sign column, the bit of weight 2^left_digit is in column EXTRA_PREPAD
+    fn print_significand(f: &mut dyn Write, sign: char, sig: &BitVec, prepad: usize, postpad: usize) -> std::fmt::Result {
+      let mut row = String::new();
+      write!(row, "{}{}{}{}", sign, " ".repeat(prepad - 1), PINK, bit2char(*sig.last().unwrap()))?;
+      write!(row, "{}", YELLOW)?;
+      print_bitset(&mut row, &sig[0..sig.len() - 1])?;
+      if !colors_enabled() {
+        // the implicit bits can't be told apart by color, so a binary point follows the 2^left_digit
+        // column in every row, and smaller numbers are padded with zeros up to it to keep the columns aligned
+        let first_digit = row[1..].find(['0', '1']).unwrap() + 1;
+        if first_digit > EXTRA_PREPAD {
+          row.replace_range(EXTRA_PREPAD..first_digit, &"0".repeat(first_digit - EXTRA_PREPAD));
+        }
+        row.insert(EXTRA_PREPAD + 1, '.');
+      }
+      writeln!(f, "{}{}{}...{}", row, DARK_GRAY, "0".repeat(postpad), RESET)
     }
-    print_significand(f, &a_sig, (left_digit - a_exp) as usize + EXTRA_PREPAD, (a_exp - a_sig.len() as i64 - right_digit) as usize + EXTRA_POSTPAD)?;
-    print_significand(f, &b_sig, (left_digit - b_exp) as usize + EXTRA_PREPAD, (b_exp - a_sig.len() as i64 - right_digit) as usize + EXTRA_POSTPAD)?;
+    print_significand(f, ' ', &a_sig, (left_digit - a_exp) as usize + EXTRA_PREPAD, (a_lsb - right_digit) as usize + EXTRA_POSTPAD)?;
+    print_significand(f, ' ', &b_sig, (left_digit - b_exp) as usize + EXTRA_PREPAD, (b_lsb - right_digit) as usize + EXTRA_POSTPAD)?;
     //todo account for diff too large
 
-    writeln!(f, "-{}-", "-".repeat(diff))?;
+    writeln!(f, "-{}-{}", "-".repeat(diff + EXTRA_PREPAD), if colors_enabled() { "" } else { "-" })?;
 
-    let mut ai = BigInt::from_slice(Sign::Plus, a_sig.as_raw_slice());
-    ai <<= a_exp - min_exp;
-    let mut bi = BigInt::from_slice(Sign::Plus, b_sig.as_raw_slice());
-    bi <<= b_exp - min_exp;
+    // both significands as integers in units of 2^right_digit
+    let ai = BigInt::from(bits_to_biguint(&a_sig)) << (a_lsb - right_digit);
+    let bi = BigInt::from(bits_to_biguint(&b_sig)) << (b_lsb - right_digit);
     let qi = if sub { ai - bi } else { ai + bi };
-    let mut q_sig = BitVec::from_vec(qi.to_u32_digits().1);
+    let mut q_sig = BitVec::from_vec(qi.magnitude().to_u32_digits());
     // remove zeroes from the MSB end
-    if let Some(last_one) = q_sig.last_one() {
-      q_sig.truncate(last_one + 1);
+    match q_sig.last_one() {
+      Some(last_one) => q_sig.truncate(last_one + 1),
+      None => q_sig.resize(1, false),
     }
 
-    let q_sign = qi.sign() == Sign::Minus;
-    if q_sign {
-      write!(f, "-")?;
+    let q_sign = if sub {
+      match qi.sign() {
+        Sign::Minus => true,
+        Sign::Plus => false,
+        // an exact zero difference is positive, except when rounding toward negative
+        Sign::NoSign => env.rounding_mode == RoundingMode::TowardNegative,
+      }
     } else {
-      write!(f, " ")?;
-    }
-    print_significand(f, &q_sig, diff + EXTRA_PREPAD - 1 - q_sig.len(), EXTRA_POSTPAD)?;
-
-    // compute q_exp before messing with the significand
-    let q_exp = right_digit + q_sig.len() as i64;
-    // pad zeroes on the LSB end until it's long enough
-    // todo n^2
-    while q_sig.len() < output_type.sig_bits + 1 {
-      q_sig.insert(0, false);
-    }
+      a.sign()
+    };
+    let q_msb = right_digit + q_sig.len() as i64 - 1;
+    print_significand(f, if q_sign { '-' } else { ' ' }, &q_sig, (left_digit - q_msb + EXTRA_PREPAD as i64) as usize, EXTRA_POSTPAD)?;
 
     writeln!(f, "\n3. Round to destination format.\n")?;
-    writeln!(f, "- The current rounding mode is: {:?}", env.rounding_mode)?;
-    let q_chop = q_sig.len().saturating_sub(output_type.sig_bits).saturating_sub(1);
-    let q_sig_truncated = &q_sig[q_chop..];
-    let q_low_bits = q_sig[..q_chop].any();
-    // at least 2^emax * (2 - ulp) => wraps to inf
-    // equivalent to if exp = max_exp && all sig bits are 1
-    if q_exp > output_type.max_exp() || q_exp == output_type.max_exp() && q_sig_truncated.all() {
-      // overflow
-      writeln!(f, "- The output is too large, wrapping to infinity")?;
-      Ok((Float::inf(output_type, a.sign()), Exception::OVERFLOW))
-    } else if q_exp < output_type.min_exp() {
-      // subnormal
-      writeln!(f, "- The raw exponent of the output is {}, but the minimum possible exponent is {}, encoding as subnormal", q_exp, output_type.min_exp())?;
-      let shift = output_type.min_exp() - q_exp;
-      let mut q_sig = q_sig_truncated.to_owned();
-      q_sig.shift_left(shift as usize); // this shifts towards zero
-      Ok((Float::from_parts(output_type, q_sign, output_type.min_exp(), &q_sig[..output_type.sig_bits]), Exception::default()))
-    } else {
-      // normal
-      writeln!(f, "- The raw exponent of the output is {}, encoding as normal", q_exp)?;
-      Ok((Float::from_parts(output_type, q_sign, q_exp, &q_sig_truncated[..output_type.sig_bits]), Exception::default()))
-    }
-
-    //todo round properly
+    let q_value = BigRational::from_integer(BigInt::from(qi.magnitude().clone())) * pow2(right_digit);
+    env.round(f, q_sign, &q_value, output_type).map(float_result)
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+use std::fmt::Write;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{Float, FloatClass, FloatParameters};
+use crate::ops::{floats, propagate_nan, to_output_type, Exception, Op, Value};
+
+fn describe(ordering: Ordering) -> &'static str {
+  match ordering {
+    Ordering::Less => "A < B",
+    Ordering::Equal => "A = B",
+    Ordering::Greater => "A > B",
+  }
+}
+
+/// Compares two non-NaN floats, with -0 ordered below +0.
+fn signed_zero_cmp(a: &Float, b: &Float) -> Ordering {
+  match a.compare(b).unwrap() {
+    Ordering::Equal => b.sign().cmp(&a.sign()),
+    ordering => ordering,
+  }
+}
+
+/// `totalOrder(A, B)`, which is true if A orders before or equal to B in the total order of encodings.
+pub struct TotalOrder;
+
+impl Op for TotalOrder {
+  fn num_params(&self) -> usize {
+    2
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, _env: &FloatingPointEnv, params: &[Value], _output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+    let b = &params[1];
+    writeln!(f, "\n1. Classify inputs\n")?;
+    writeln!(f, "- The total order is -NaN < -Inf < negative numbers < -0 < +0 < positive numbers < +Inf < +NaN")?;
+    if a.params() != b.params() {
+      writeln!(f, "- The inputs have different formats, totalOrder is only defined within a format")?;
+    }
+    let (a_nan, b_nan) = (a.classify().nan(), b.classify().nan());
+    if a_nan && b_nan && a.sign() == b.sign() {
+      writeln!(f, "- Both inputs are NaN with the same sign, signalling NaNs order closer to zero than quiet NaNs, then the payload decides")?;
+    } else if a_nan || b_nan {
+      writeln!(f, "- NaN inputs order by their sign, beyond the infinities")?;
+    } else if a.classify().zero() && b.classify().zero() && a.sign() != b.sign() {
+      writeln!(f, "- Both inputs are zero, the sign decides: -0 orders before +0")?;
+    } else {
+      writeln!(f, "- Both inputs are numbers, their values decide")?;
+    }
+
+    writeln!(f, "\n2. Compare\n")?;
+    let ordering = a.total_cmp(b);
+    let result = ordering != Ordering::Greater;
+    writeln!(f, "- {} in the total order, return {}", describe(ordering), result)?;
+    Ok((Value::Bool(result), Exception::default()))
+  }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Predicate {
+  Equal,
+  NotEqual,
+  Greater,
+  GreaterEqual,
+  Less,
+  LessEqual,
+  Unordered,
+  Ordered,
+}
+
+impl Predicate {
+  fn test(self, ordering: Option<Ordering>) -> bool {
+    match (self, ordering) {
+      (Predicate::Unordered, v) => v.is_none(),
+      (Predicate::Ordered, v) => v.is_some(),
+      // the only predicate that is true for unordered inputs
+      (Predicate::NotEqual, v) => v != Some(Ordering::Equal),
+      (_, None) => false,
+      (Predicate::Equal, Some(v)) => v.is_eq(),
+      (Predicate::Greater, Some(v)) => v.is_gt(),
+      (Predicate::GreaterEqual, Some(v)) => v.is_ge(),
+      (Predicate::Less, Some(v)) => v.is_lt(),
+      (Predicate::LessEqual, Some(v)) => v.is_le(),
+    }
+  }
+}
+
+/// The `compareQuiet*` and `compareSignaling*` predicates.
+/// The quiet ones only signal invalid operation for signalling NaNs, the signaling ones for any NaN.
+pub struct Compare {
+  pub predicate: Predicate,
+  pub signaling: bool,
+}
+
+impl Op for Compare {
+  fn num_params(&self) -> usize {
+    2
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, _env: &FloatingPointEnv, params: &[Value], _output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+    let b = &params[1];
+    writeln!(f, "\n1. Classify inputs\n")?;
+    let mut exception = Exception::default();
+    if params.iter().any(|v| v.classify() == FloatClass::SignallingNaN) {
+      writeln!(f, "- An input is a signalling NaN, signal invalid operation")?;
+      exception = Exception::INVALID_OPERATION;
+    } else if params.iter().any(|v| v.classify().nan()) {
+      if self.signaling {
+        writeln!(f, "- An input is NaN and the comparison is signaling, signal invalid operation")?;
+        exception = Exception::INVALID_OPERATION;
+      } else {
+        writeln!(f, "- An input is a quiet NaN and the comparison is quiet, no exception")?;
+      }
+    }
+    if a.classify().zero() && b.classify().zero() {
+      writeln!(f, "- Both inputs are zero, -0 and +0 compare equal")?;
+    }
+
+    writeln!(f, "\n2. Compare\n")?;
+    let ordering = a.compare(b);
+    match ordering {
+      Some(v) => writeln!(f, "- {}", describe(v))?,
+      None => writeln!(f, "- The inputs are unordered, only NotEqual and Unordered are true")?,
+    }
+    let result = self.predicate.test(ordering);
+    writeln!(f, "- {:?} is {}", self.predicate, result)?;
+    Ok((Value::Bool(result), exception))
+  }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MinMaxKind {
+  /// IEEE 754-2019 `minimum`/`maximum`, NaN inputs propagate.
+  Propagate,
+  /// IEEE 754-2019 `minimumNumber`/`maximumNumber`, numbers win over NaNs.
+  Number,
+  /// IEEE 754-2008 `minNum`/`maxNum`, quiet NaNs lose but signalling NaNs return NaN.
+  Legacy,
+}
+
+/// The min/max family, which differ in how they treat NaN inputs and signed zeros.
+pub struct MinMax {
+  pub kind: MinMaxKind,
+  pub max: bool,
+}
+
+impl Op for MinMax {
+  fn num_params(&self) -> usize {
+    2
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+    let b = &params[1];
+    writeln!(f, "\n1. Classify inputs\n")?;
+    let mut exception = Exception::default();
+    match self.kind {
+      MinMaxKind::Propagate => {
+        if let Some(ret) = propagate_nan(f, params, output_type)? {
+          return Ok(ret);
+        }
+      }
+      MinMaxKind::Number => {
+        if params.iter().any(|v| v.classify() == FloatClass::SignallingNaN) {
+          writeln!(f, "- An input is a signalling NaN, signal invalid operation")?;
+          exception = Exception::INVALID_OPERATION;
+        }
+        match (a.classify().nan(), b.classify().nan()) {
+          (true, true) => {
+            writeln!(f, "- Both inputs are NaN, return NaN")?;
+            return Ok((Float::nan(output_type).into(), exception));
+          }
+          (true, false) => {
+            writeln!(f, "- Input A is NaN, the number B is returned")?;
+            return to_output_type(f, env, 3, b, exception, output_type);
+          }
+          (false, true) => {
+            writeln!(f, "- Input B is NaN, the number A is returned")?;
+            return to_output_type(f, env, 3, a, exception, output_type);
+          }
+          (false, false) => {}
+        }
+      }
+      MinMaxKind::Legacy => {
+        if params.iter().any(|v| v.classify() == FloatClass::SignallingNaN) {
+          writeln!(f, "- An input is a signalling NaN, return NaN and signal invalid operation")?;
+          return Ok((Float::nan(output_type).into(), Exception::INVALID_OPERATION));
+        }
+        match (a.classify().nan(), b.classify().nan()) {
+          (true, true) => {
+            writeln!(f, "- Both inputs are quiet NaN, return NaN")?;
+            return Ok((Float::nan(output_type).into(), exception));
+          }
+          (true, false) => {
+            writeln!(f, "- Input A is a quiet NaN, B is returned")?;
+            return to_output_type(f, env, 3, b, exception, output_type);
+          }
+          (false, true) => {
+            writeln!(f, "- Input B is a quiet NaN, A is returned")?;
+            return to_output_type(f, env, 3, a, exception, output_type);
+          }
+          (false, false) => {}
+        }
+      }
+    }
+    writeln!(f, "- Both inputs are numbers")?;
+
+    writeln!(f, "\n2. Compare\n")?;
+    let both_zero = a.classify().zero() && b.classify().zero() && a.sign() != b.sign();
+    let ordering = if both_zero && self.kind == MinMaxKind::Legacy {
+      writeln!(f, "- Both inputs are zero, 2008 leaves the sign of the result unspecified, A is returned")?;
+      return to_output_type(f, env, 3, a, exception, output_type);
+    } else if both_zero {
+      writeln!(f, "- Both inputs are zero, -0 orders below +0")?;
+      signed_zero_cmp(a, b)
+    } else {
+      a.compare(b).unwrap()
+    };
+    let take_a = if self.max { ordering.is_ge() } else { ordering.is_le() };
+    writeln!(f, "- {}, return {}", describe(ordering), if take_a { "A" } else { "B" })?;
+    to_output_type(f, env, 3, if take_a { a } else { b }, exception, output_type)
+  }
+}
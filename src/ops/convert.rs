@@ -0,0 +1,33 @@
+use std::fmt::Write;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{Float, FloatParameters};
+use crate::ops::{floats, float_result, propagate_nan, Exception, Op, Value};
+
+/// Converts a float to the output format.
+pub struct Convert;
+
+impl Op for Convert {
+  fn num_params(&self) -> usize {
+    1
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+
+    writeln!(f, "\n1. Classify input\n")?;
+    if let Some(ret) = propagate_nan(f, params, output_type)? {
+      return Ok(ret);
+    }
+    let a_class = a.classify();
+    if a_class.inf() {
+      writeln!(f, "- Input is infinite, return infinity of the same sign")?;
+      return Ok((Float::inf(output_type, a.sign()).into(), Exception::default()));
+    }
+    writeln!(f, "- Input is finite")?;
+
+    writeln!(f, "\n2. Round to destination format.\n")?;
+    writeln!(f, "- Converting from {} to {}", a.params(), output_type)?;
+    env.round(f, a.sign(), &a.exact_value().unwrap(), output_type).map(float_result)
+  }
+}
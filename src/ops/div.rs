@@ -0,0 +1,72 @@
+use std::fmt::Write;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{bits_to_biguint, pow2, Float, FloatParameters};
+use crate::ops::{floats, float_result, flush_inputs, format_binary_sci, propagate_nan, Exception, Op, Value};
+use crate::str_conv::format_binary_point;
+
+pub struct Div;
+
+impl Op for Div {
+  fn num_params(&self) -> usize {
+    2
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    writeln!(f, "\n1. Classify inputs\n")?;
+    let params = flush_inputs(f, env, params)?;
+    if let Some(ret) = propagate_nan(f, &params, output_type)? {
+      return Ok(ret);
+    }
+    let a = &params[0];
+    let b = &params[1];
+    let a_class = a.classify();
+    let b_class = b.classify();
+    let sign = a.sign() ^ b.sign();
+    let signed = |s: &str| format!("{}{}", if sign { "-" } else { "" }, s);
+
+    if a_class.inf() && b_class.inf() {
+      writeln!(f, "- Operation is Infinity / Infinity, return NaN")?;
+      return Ok((Float::nan(output_type).into(), Exception::INVALID_OPERATION));
+    }
+    if a_class.zero() && b_class.zero() {
+      writeln!(f, "- Operation is 0 / 0, return NaN")?;
+      return Ok((Float::nan(output_type).into(), Exception::INVALID_OPERATION));
+    }
+    if a_class.inf() {
+      writeln!(f, "- Operation is Infinity / Finite, return {}", signed("Infinity"))?;
+      return Ok((Float::inf(output_type, sign).into(), Exception::default()));
+    }
+    if b_class.zero() {
+      writeln!(f, "- Operation is Nonzero / 0, return {} and signal division by zero", signed("Infinity"))?;
+      return Ok((Float::inf(output_type, sign).into(), Exception::DIVISION_BY_ZERO));
+    }
+    if b_class.inf() {
+      writeln!(f, "- Operation is Finite / Infinity, return {}", signed("0"))?;
+      return Ok((Float::from_fields(output_type, sign, 0, &Zero::zero()).into(), Exception::default()));
+    }
+    writeln!(f, "- Both inputs are finite")?;
+
+    writeln!(f, "\n2. Divide significands and subtract exponents\n")?;
+    writeln!(f, "- The sign of the result is {} xor {} = {}", a.sign() as u8, b.sign() as u8, sign as u8)?;
+    let a_sig = BigInt::from(bits_to_biguint(&a.significand_logical()));
+    let b_sig = BigInt::from(bits_to_biguint(&b.significand_logical()));
+    let exp = a.exponent_logical() - b.exponent_logical();
+    // scale the significands to the same number of fraction bits
+    let frac_diff = b.params().sig_bits as i64 - a.params().sig_bits as i64;
+    let quotient = BigRational::new(a_sig, b_sig) * pow2(frac_diff);
+    // show a few more bits than the output can hold
+    let shown_bits = output_type.sig_bits + 3;
+    let shown = (&quotient * pow2(shown_bits as i64)).floor();
+    let exact = shown == &quotient * pow2(shown_bits as i64);
+    writeln!(f, "  {}", format_binary_sci(a))?;
+    writeln!(f, "/ {}", format_binary_sci(b))?;
+    writeln!(f, "= {}{} × 2^{}", format_binary_point(shown.numer().magnitude(), shown_bits), if exact { "" } else { "..." }, exp)?;
+
+    writeln!(f, "\n3. Round to destination format.\n")?;
+    env.round(f, sign, &(quotient * pow2(exp)), output_type).map(float_result)
+  }
+}
@@ -0,0 +1,293 @@
+use std::fmt::Write;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{pow2, Float, FloatParameters};
+use crate::ints::{Integer, I32_TYPE};
+use crate::ops::{float_result, format_binary_sci, propagate_nan, to_output_type, Exception, Kind, Op, Value};
+
+/// Explains how the exponent of a finite nonzero float is read from its exponent field.
+fn explain_exponent(f: &mut dyn Write, a: &Float) -> Result<i64, std::fmt::Error> {
+  let params = a.params();
+  let exp = a.normalized_exponent();
+  if a.classify().subnormal() {
+    let shift = params.min_exp() - exp;
+    writeln!(f, "- The exponent field is 0, so A is subnormal: {}", format_binary_sci(a))?;
+    writeln!(f, "- The leading 1 is {} bits below the binary point, so the exponent is {} - {} = {}", shift, params.min_exp(), shift, exp)?;
+  } else {
+    writeln!(f, "- The exponent field is {}, minus the bias {} gives the exponent {}", a.exponent_bits_integer(), params.exp_bias, exp)?;
+  }
+  Ok(exp)
+}
+
+/// Converts an integer that is exactly representable in most formats to a float.
+fn integer_to_float(f: &mut dyn Write, env: &FloatingPointEnv, value: i64, output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+  env.round(f, value < 0, &BigRational::from_integer(BigInt::from(value.abs())), output_type).map(float_result)
+}
+
+/// `scaleB(A, N)`, also known as `ldexp`, which computes A × 2^N with a single rounding.
+pub struct ScaleB;
+
+impl Op for ScaleB {
+  fn num_params(&self) -> usize {
+    2
+  }
+
+  fn param_kinds(&self) -> Vec<Kind> {
+    vec![Kind::Float, Kind::Int]
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let a = params[0].float().expect("operand is not a float");
+    let scale = &params[1].int().expect("operand is not an integer").value;
+    writeln!(f, "\n1. Classify inputs\n")?;
+    if let Some(ret) = propagate_nan(f, std::slice::from_ref(a), output_type)? {
+      return Ok(ret);
+    }
+    let class = a.classify();
+    if class.inf() || class.zero() {
+      writeln!(f, "- Input A is {}, scaling doesn't change it", if class.inf() { "infinite" } else { "zero" })?;
+      return to_output_type(f, env, 2, a, Exception::default(), output_type);
+    }
+    writeln!(f, "- Input A is finite")?;
+
+    writeln!(f, "\n2. Add N to the exponent\n")?;
+    let params = a.params();
+    let exp = explain_exponent(f, a)?;
+    // beyond these the result overflows, or is below half the smallest subnormal and rounds to it or zero,
+    // so larger scales give the same result. Exponents have at most 60 bits, so the bounds fit in an i64.
+    let tiny_exp = output_type.min_exp() - output_type.sig_bits as i64 - 2;
+    let lowest = tiny_exp - exp;
+    let highest = output_type.max_exp() + 1 - exp;
+    let n = scale.clamp(&BigInt::from(lowest), &BigInt::from(highest)).to_i64().unwrap();
+    if &BigInt::from(n) != scale {
+      writeln!(f, "- N is far outside the exponent range, scaling by 2^{} gives the same result", n)?;
+    }
+    let new_exp = exp + n;
+    writeln!(f, "- The exponent becomes {} + {} = {}", exp, n, new_exp)?;
+    let in_range = new_exp >= output_type.min_exp() && new_exp <= output_type.max_exp();
+    if class.normal() && params == output_type && in_range {
+      let raw = a.exponent_bits_integer() as i64;
+      let max_field = (1u64 << params.exp_bits) - 2;
+      writeln!(f, "- The exponent field becomes {} + {} = {}, which is within the normal range 1 to {}", raw, n, raw + n, max_field)?;
+      writeln!(f, "- Only the exponent field changes, the significand is kept and the result is exact")?;
+      return Ok((Float::from_parts(params, a.sign(), new_exp, a.significand_bits()).into(), Exception::default()));
+    }
+    if new_exp > output_type.max_exp() {
+      writeln!(f, "- {} is above the maximum exponent {}, the result overflows", new_exp, output_type.max_exp())?;
+    } else if new_exp <= tiny_exp {
+      writeln!(f, "- {} is more than {} bits below the minimum exponent {}, the result is less than half the smallest subnormal", new_exp, output_type.sig_bits + 1, output_type.min_exp())?;
+    } else if new_exp < output_type.min_exp() {
+      let shift = output_type.min_exp() - new_exp;
+      writeln!(f, "- {} is below the minimum exponent {}, so the result is subnormal", new_exp, output_type.min_exp())?;
+      writeln!(f, "- The significand is shifted right by {} bits, and the bits shifted out must be rounded", shift)?;
+    } else if class.subnormal() {
+      writeln!(f, "- The result is normal, the significand is shifted left to put the leading 1 in front of the binary point")?;
+    } else {
+      writeln!(f, "- The result is converted from {} to {}", params, output_type)?;
+    }
+
+    writeln!(f, "\n3. Round to destination format.\n")?;
+    // out of range, the sign is all that matters, and the exact value can be huge in wide formats
+    if new_exp > output_type.max_exp() || new_exp <= tiny_exp {
+      writeln!(f, "- The current rounding mode is: {:?}", env.rounding_mode)?;
+      let result = if new_exp > output_type.max_exp() { env.overflow(f, a.sign(), output_type) } else { env.underflow(f, a.sign(), output_type) };
+      return result.map(float_result);
+    }
+    let value = a.exact_value().unwrap() * pow2(n);
+    env.round(f, a.sign(), &value, output_type).map(float_result)
+  }
+}
+
+/// `logB(A)`, the exponent of A. `ilogB` returns it as an integer, as C's `ilogb`, and `logB` as a float, as C's `logb`.
+pub struct LogB {
+  pub int: bool,
+}
+
+impl Op for LogB {
+  fn num_params(&self) -> usize {
+    1
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let a = params[0].float().expect("operand is not a float");
+    let class = a.classify();
+    writeln!(f, "\n1. Classify input\n")?;
+    if self.int && (!class.finite() || class.zero()) {
+      // the values of FP_ILOGB0, FP_ILOGBNAN and INT_MAX in glibc
+      let (desc, value) = if class.nan() {
+        ("NaN, return FP_ILOGBNAN", I32_TYPE.min())
+      } else if class.inf() {
+        ("infinite, return INT_MAX", I32_TYPE.max())
+      } else {
+        ("zero, return FP_ILOGB0", I32_TYPE.min())
+      };
+      writeln!(f, "- Input is {} and signal invalid operation", desc)?;
+      writeln!(f, "- The values are implementation defined, these are glibc's")?;
+      return Ok((Value::Int(Integer { value, ty: I32_TYPE }), Exception::INVALID_OPERATION));
+    }
+    if let Some(ret) = propagate_nan(f, std::slice::from_ref(a), output_type)? {
+      return Ok(ret);
+    }
+    if class.inf() {
+      writeln!(f, "- Input is infinite, return +Infinity")?;
+      return Ok((Float::inf(output_type, false).into(), Exception::default()));
+    }
+    if class.zero() {
+      writeln!(f, "- Input is zero, return -Infinity and signal division by zero")?;
+      return Ok((Float::inf(output_type, true).into(), Exception::DIVISION_BY_ZERO));
+    }
+    writeln!(f, "- Input is finite")?;
+
+    writeln!(f, "\n2. Read the exponent\n")?;
+    let exp = explain_exponent(f, a)?;
+    if self.int {
+      return Ok((Value::Int(Integer { value: BigInt::from(exp), ty: I32_TYPE }), Exception::default()));
+    }
+    writeln!(f, "\n3. Convert the exponent to {}\n", output_type)?;
+    integer_to_float(f, env, exp, output_type)
+  }
+}
+
+/// `frexp(A)`, which splits A into a fraction with magnitude in [0.5, 1) and a power of two.
+pub struct Frexp;
+
+impl Op for Frexp {
+  fn num_params(&self) -> usize {
+    1
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let a = params[0].float().expect("operand is not a float");
+    let class = a.classify();
+    let zero_exp = Value::Int(Integer { value: BigInt::from(0), ty: I32_TYPE });
+    writeln!(f, "\n1. Classify input\n")?;
+    if let Some((fraction, exception)) = propagate_nan(f, std::slice::from_ref(a), output_type)? {
+      writeln!(f, "- The exponent is unspecified, glibc returns 0")?;
+      return Ok((Value::Tuple(vec![("fraction", fraction), ("exponent", zero_exp)]), exception));
+    }
+    if class.inf() || class.zero() {
+      writeln!(f, "- Input is {}, the fraction is the input itself", if class.inf() { "infinite" } else { "zero" })?;
+      if class.inf() {
+        writeln!(f, "- The exponent is unspecified, glibc returns 0")?;
+      }
+      let (fraction, exception) = to_output_type(f, env, 2, a, Exception::default(), output_type)?;
+      return Ok((Value::Tuple(vec![("fraction", fraction), ("exponent", zero_exp)]), exception));
+    }
+    writeln!(f, "- Input is finite")?;
+
+    writeln!(f, "\n2. Split off the exponent\n")?;
+    let exp = explain_exponent(f, a)?;
+    writeln!(f, "- Moving the binary point in front of the leading 1 gives 0.1... × 2^{}, so the exponent is {}", exp + 1, exp + 1)?;
+    writeln!(f, "- The fraction is A × 2^{}", -(exp + 1))?;
+
+    writeln!(f, "\n3. Round to destination format.\n")?;
+    let value = a.exact_value().unwrap() * pow2(-(exp + 1));
+    let (fraction, exception) = env.round(f, a.sign(), &value, output_type)?;
+    let exponent = Value::Int(Integer { value: BigInt::from(exp + 1), ty: I32_TYPE });
+    Ok((Value::Tuple(vec![("fraction", fraction.into()), ("exponent", exponent)]), exception))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bitvec::field::BitField;
+  use super::*;
+  use crate::fenv::RoundingMode;
+  use crate::floats::{F32_PARAMS, F64_PARAMS};
+  use crate::ints::IntType;
+
+  fn f32(s: &str) -> Value {
+    Float::parse(s, &F32_PARAMS).unwrap().into()
+  }
+
+  fn int(v: i64) -> Value {
+    Value::Int(Integer { value: BigInt::from(v), ty: I32_TYPE })
+  }
+
+  fn bits(v: &Value) -> u64 {
+    v.float().unwrap().bits().load_le::<u64>()
+  }
+
+  fn frexp(a: &str) -> (u64, BigInt) {
+    let (value, _) = Frexp.execute(&FloatingPointEnv::default(), &[f32(a)], &F32_PARAMS);
+    let Value::Tuple(values) = value else { panic!("frexp didn't return a tuple") };
+    assert_eq!(values.iter().map(|(name, _)| *name).collect::<Vec<_>>(), ["fraction", "exponent"]);
+    (bits(&values[0].1), values[1].1.int().unwrap().value.clone())
+  }
+
+  fn ldexp(a: &str, n: i64, output_type: &FloatParameters) -> (Value, Exception) {
+    ScaleB.execute(&FloatingPointEnv::default(), &[f32(a), int(n)], output_type)
+  }
+
+  #[test]
+  fn frexp_splits_the_exponent() {
+    // 12 = .75 × 2^4
+    assert_eq!(frexp("12"), (0x3f400000, BigInt::from(4)));
+    assert_eq!(frexp("-0.5"), (0xbf000000, BigInt::from(0)));
+    // the smallest subnormal is .5 × 2^-148
+    assert_eq!(frexp("0x00000001"), (0x3f000000, BigInt::from(-148)));
+    assert_eq!(frexp("-0"), (0x80000000, BigInt::from(0)));
+    assert_eq!(frexp("inf"), (0x7f800000, BigInt::from(0)));
+  }
+
+  #[test]
+  fn frexp_propagates_nan() {
+    let (value, exception) = Frexp.execute(&FloatingPointEnv::default(), &[f32("0x7f800001")], &F32_PARAMS);
+    let Value::Tuple(values) = value else { panic!("frexp didn't return a tuple") };
+    assert!(values[0].1.float().unwrap().classify().nan());
+    assert_eq!(exception, Exception::INVALID_OPERATION);
+    let (_, exception) = Frexp.execute(&FloatingPointEnv::default(), &[f32("nan")], &F32_PARAMS);
+    assert_eq!(exception, Exception::default());
+  }
+
+  #[test]
+  fn ldexp_scales_by_a_power_of_two() {
+    assert_eq!(bits(&ldexp("0.75", 4, &F32_PARAMS).0), 0x41400000);
+    assert_eq!(bits(&ldexp("1", -149, &F32_PARAMS).0), 0x00000001);
+    assert_eq!(bits(&ldexp("0x00000001", 149, &F32_PARAMS).0), 0x3f800000);
+    // the result format can hold exponents the input format can't
+    assert_eq!(bits(&ldexp("1", 200, &F64_PARAMS).0), 0x4c70000000000000);
+  }
+
+  #[test]
+  fn ldexp_rounds_out_of_range_results() {
+    let (value, exception) = ldexp("1", 128, &F32_PARAMS);
+    assert_eq!((bits(&value), exception), (0x7f800000, Exception::OVERFLOW | Exception::INEXACT));
+    // 3 × 2^-150 rounds to the even 2 × 2^-149
+    let (value, exception) = ldexp("3", -150, &F32_PARAMS);
+    assert_eq!(bits(&value), 0x00000002);
+    assert!(exception.contains(Exception::UNDERFLOW | Exception::INEXACT));
+    // huge scales are clamped
+    assert_eq!(bits(&ldexp("1", i64::MIN, &F32_PARAMS).0), 0);
+    let (value, exception) = ldexp("0x00000001", i64::MAX, &F32_PARAMS);
+    assert_eq!((bits(&value), exception), (0x7f800000, Exception::OVERFLOW | Exception::INEXACT));
+    let up = FloatingPointEnv { rounding_mode: RoundingMode::TowardPositive, ..Default::default() };
+    let (value, _) = ScaleB.execute(&up, &[f32("3e38"), int(-1000)], &F32_PARAMS);
+    assert_eq!(bits(&value), 0x00000001);
+  }
+
+  #[test]
+  fn ldexp_in_wide_formats() {
+    let wide = FloatParameters::parse("custom(60, 3)").unwrap();
+    let one = Value::from(Float::parse("1", &wide).unwrap());
+    let (value, exception) = ScaleB.execute(&FloatingPointEnv::default(), &[one.clone(), int(5)], &wide);
+    assert_eq!(value.float().unwrap().exact_value(), Some(pow2(5)));
+    assert_eq!(exception, Exception::default());
+    let huge = Value::Int(Integer { value: BigInt::from(i64::MAX), ty: IntType { bits: 64, signed: true } });
+    let (value, exception) = ScaleB.execute(&FloatingPointEnv::default(), &[one, huge], &wide);
+    assert!(value.float().unwrap().classify().inf());
+    assert!(exception.contains(Exception::OVERFLOW));
+  }
+
+  #[test]
+  fn ldexp_inverts_frexp() {
+    for a in ["12", "-0.1", "3e38", "0x00000003"] {
+      let (fraction, exponent) = frexp(a);
+      let fraction = Value::from(Float::parse(&format!("0x{:08x}", fraction), &F32_PARAMS).unwrap());
+      let (value, _) = ScaleB.execute(&FloatingPointEnv::default(), &[fraction, int(exponent.to_i64().unwrap())], &F32_PARAMS);
+      assert_eq!(bits(&value), bits(&f32(a)), "{}", a);
+    }
+  }
+}
@@ -0,0 +1,100 @@
+use std::fmt::Write;
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{Float, FloatParameters};
+use crate::ops::{float_result, Exception, Kind, Op, Value};
+use crate::str_conv::format_binary_point;
+
+/// Converts an integer to the output format, as the IEEE 754 `convertFromInt` operation.
+pub struct FromInt;
+
+impl Op for FromInt {
+  fn num_params(&self) -> usize {
+    1
+  }
+
+  fn param_kinds(&self) -> Vec<Kind> {
+    vec![Kind::Int]
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let a = params[0].int().expect("operand is not an integer");
+    writeln!(f, "\n1. Classify input\n")?;
+    writeln!(f, "- Input is {} as {}, encoded as {}", a.value, a.ty, a.bit_string())?;
+    if a.value.is_zero() {
+      writeln!(f, "- Input is zero, return +0")?;
+      return Ok((Float::zero(output_type).into(), Exception::default()));
+    }
+    let sign = a.value.is_negative();
+    let magnitude = a.value.magnitude();
+    if sign {
+      writeln!(f, "- Input is negative, the sign bit is set and the magnitude {} is converted", magnitude)?;
+    }
+
+    writeln!(f, "\n2. Normalize\n")?;
+    let msb = magnitude.bits() as usize - 1;
+    let width = a.ty.bits;
+    writeln!(f, "- The magnitude in {} bits is {:0width$b}", width, magnitude, width = width)?;
+    writeln!(f, "- The most significant set bit is bit {}, so there are {} leading zeros", msb, width - msb - 1)?;
+    writeln!(f, "- Shifting the binary point behind the leading 1: {} × 2^{}", format_binary_point(magnitude, msb), msb)?;
+    writeln!(f, "- The exponent is {}", msb)?;
+    let p = output_type.sig_bits;
+    if msb > p {
+      let lost = msb - p;
+      let low = magnitude & ((BigUint::one() << lost) - 1u32);
+      let unit = if lost == 1 { "bit" } else { "bits" };
+      writeln!(f, "- {} only keeps {} bits after the leading 1, the low {} {} {:0lost$b} must be rounded away", output_type, p, lost, unit, low, lost = lost)?;
+    } else {
+      writeln!(f, "- All bits fit in the {} bits after the leading 1 that {} keeps", p, output_type)?;
+    }
+
+    writeln!(f, "\n3. Round to destination format.\n")?;
+    env.round(f, sign, &BigRational::from_integer(BigInt::from(magnitude.clone())), output_type).map(float_result)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::fenv::RoundingMode;
+  use crate::floats::F32_PARAMS;
+  use crate::ints::{IntType, Integer};
+
+  fn from_int(value: &str, ty: &str, env: &FloatingPointEnv) -> (Float, Exception) {
+    let a = Integer::parse(value, &IntType::parse(ty).unwrap()).unwrap();
+    let (value, exception) = FromInt.execute(env, &[Value::Int(a)], &F32_PARAMS);
+    (value.float().unwrap().clone(), exception)
+  }
+
+  fn value(v: i64) -> Option<BigRational> {
+    Some(BigRational::from_integer(v.into()))
+  }
+
+  #[test]
+  fn exact_conversions() {
+    let env = FloatingPointEnv::default();
+    let (zero, exception) = from_int("0", "i32", &env);
+    assert!(zero.classify().zero() && !zero.sign());
+    assert_eq!(exception, Exception::default());
+    assert_eq!(from_int("-5", "i32", &env).0.exact_signed_value(), value(-5));
+    assert_eq!(from_int("0xff", "i8", &env).0.exact_signed_value(), value(-1));
+    assert_eq!(from_int("16777216", "i32", &env).1, Exception::default());
+  }
+
+  #[test]
+  fn rounds_wide_integers() {
+    let env = FloatingPointEnv::default();
+    // 2^24 + 1 is a tie between 2^24 and 2^24 + 2, ties to even rounds down
+    let (v, exception) = from_int("16777217", "i32", &env);
+    assert_eq!(v.exact_signed_value(), value(16777216));
+    assert_eq!(exception, Exception::INEXACT);
+    let up = FloatingPointEnv { rounding_mode: RoundingMode::TowardPositive, ..Default::default() };
+    assert_eq!(from_int("16777217", "i32", &up).0.exact_signed_value(), value(16777218));
+    assert_eq!(from_int("-16777217", "i32", &up).0.exact_signed_value(), value(-16777216));
+    let (v, exception) = from_int(&"9".repeat(40), "i160", &env);
+    assert!(v.classify().inf());
+    assert!(exception.contains(Exception::OVERFLOW));
+  }
+}
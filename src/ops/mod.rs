@@ -1,13 +1,34 @@
 use std::collections::BTreeMap;
-use std::fmt::{Write};
+use std::fmt::{Display, Formatter, Write};
 use std::ops::{BitAnd, BitOr, BitXor};
 use std::rc::Rc;
-use thiserror::Error;
-use crate::fenv::FloatingPointEnv;
-use crate::floats::{Float, FloatParameters};
+use crate::fenv::{FloatingPointEnv, RoundingMode};
+use crate::floats::{bits_to_biguint, Float, FloatClass, FloatParameters};
+use crate::ints::{Integer, I32_TYPE};
 use crate::ops::add::AddSub;
+use crate::ops::compare::{Compare, MinMax, MinMaxKind, Predicate, TotalOrder};
+use crate::ops::convert::Convert;
+use crate::ops::div::Div;
+use crate::ops::exponent::{Frexp, LogB, ScaleB};
+use crate::ops::from_int::FromInt;
+use crate::ops::mul::Mul;
+use crate::ops::next::{Next, NextAfter};
+use crate::ops::round::RoundToIntegral;
+use crate::ops::sign::{SignBit, SignKind};
+use crate::ops::to_int::{IntSemantics, ToInt};
+use crate::str_conv::format_binary_point;
 
 pub mod add;
+pub mod compare;
+pub mod convert;
+pub mod div;
+pub mod exponent;
+pub mod from_int;
+pub mod mul;
+pub mod next;
+pub mod round;
+pub mod sign;
+pub mod to_int;
 
 #[derive(Copy, Clone, Default, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Exception(pub u32);
@@ -18,6 +39,28 @@ impl Exception {
   pub const OVERFLOW: Exception = Exception(0x4);
   pub const UNDERFLOW: Exception = Exception(0x8);
   pub const INEXACT: Exception = Exception(0x10);
+
+  pub fn contains(self, other: Exception) -> bool {
+    self.0 & other.0 == other.0
+  }
+}
+
+impl Display for Exception {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let names = [
+      (Exception::INVALID_OPERATION, "Invalid operation"),
+      (Exception::DIVISION_BY_ZERO, "Division by zero"),
+      (Exception::OVERFLOW, "Overflow"),
+      (Exception::UNDERFLOW, "Underflow"),
+      (Exception::INEXACT, "Inexact"),
+    ];
+    let raised = names.iter().filter(|(v, _)| self.contains(*v)).map(|(_, name)| *name).collect::<Vec<_>>();
+    if raised.is_empty() {
+      write!(f, "None")
+    } else {
+      write!(f, "{}", raised.join(", "))
+    }
+  }
 }
 
 macro_rules! exception_op {
@@ -36,17 +79,190 @@ exception_op!(BitOr, bitor, |);
 exception_op!(BitAnd, bitand, &);
 exception_op!(BitXor, bitxor, ^);
 
+/// The result of an operation.
+#[derive(Clone, Debug)]
+pub enum Value {
+  Float(Float),
+  /// The result of a predicate such as `totalOrder`.
+  Bool(bool),
+  Int(Integer),
+  /// Several results with their names, such as the fraction and exponent from `frexp`.
+  Tuple(Vec<(&'static str, Value)>),
+  /// An operation without a defined result, such as a trap or undefined behaviour.
+  Undefined(String),
+}
+
+impl Value {
+  /// Returns the float, if the result is one.
+  pub fn float(&self) -> Option<&Float> {
+    match self {
+      Value::Float(v) => Some(v),
+      _ => None,
+    }
+  }
+
+  /// Returns the integer, if the result is one.
+  pub fn int(&self) -> Option<&Integer> {
+    match self {
+      Value::Int(v) => Some(v),
+      _ => None,
+    }
+  }
+}
+
+impl From<Float> for Value {
+  fn from(value: Float) -> Self {
+    Value::Float(value)
+  }
+}
+
+/// Converts the result of rounding into the result of an operation.
+pub fn float_result((value, exception): (Float, Exception)) -> (Value, Exception) {
+  (Value::Float(value), exception)
+}
+
+/// The kind of value an operand must be.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Kind {
+  Float,
+  Int,
+}
+
+/// Returns the float operands of an op whose operands are all floats.
+/// Callers check the operands against [`Op::param_kinds`], so anything else is a bug.
+pub fn floats(params: &[Value]) -> Vec<Float> {
+  params.iter().map(|v| v.float().expect("operand is not a float").clone()).collect()
+}
+
 pub trait Op {
   fn num_params(&self) -> usize;
 
-  fn execute(&self, env: &FloatingPointEnv, params: &[Float], output_type: &FloatParameters) -> (Float, Exception);
+  /// The kind of each operand, all floats unless the op says otherwise.
+  fn param_kinds(&self) -> Vec<Kind> {
+    vec![Kind::Float; self.num_params()]
+  }
+
+  /// Names of the settings that can follow the operands, such as the integer type of `to-int`.
+  fn settings(&self) -> &[&'static str] {
+    &[]
+  }
+
+  /// Returns the op with the given settings, at most as many as [`Op::settings`].
+  /// Settings that aren't given keep their default.
+  fn with_settings(&self, _settings: &[String]) -> Result<Rc<dyn Op>, String> {
+    Err("this operation has no settings".to_owned())
+  }
+
+  fn execute(&self, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> (Value, Exception) {
+    self.execute_visual(&mut String::new(), env, params, output_type).unwrap()
+  }
+
+  fn execute_visual(&self, fomatter: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error>;
+}
+
+/// Propagates NaN inputs, which every arithmetic operation does before anything else.
+/// Returns `None` if no input is NaN.
+pub fn propagate_nan(f: &mut dyn Write, params: &[Float], output_type: &FloatParameters) -> Result<Option<(Value, Exception)>, std::fmt::Error> {
+  let letters = "ABCDEFG";
+  if let Some((_, name)) = params.iter().zip(letters.chars()).find(|(v, _)| v.classify() == FloatClass::SignallingNaN) {
+    writeln!(f, "- Input {} is a signalling NaN, return NaN and signal invalid operation", name)?;
+    return Ok(Some((Float::nan(output_type).into(), Exception::INVALID_OPERATION)));
+  }
+  if let Some((_, name)) = params.iter().zip(letters.chars()).find(|(v, _)| v.classify().nan()) {
+    writeln!(f, "- Input {} is NaN, return NaN", name)?;
+    return Ok(Some((Float::nan(output_type).into(), Exception::default())));
+  }
+  Ok(None)
+}
+
+/// Flushes subnormal inputs to zero if the environment requests it.
+pub fn flush_inputs(f: &mut dyn Write, env: &FloatingPointEnv, params: &[Float]) -> Result<Vec<Float>, std::fmt::Error> {
+  if env.flush_subnormals_to_zero && params.iter().any(|v| v.classify().subnormal()) {
+    writeln!(f, "- Flushing subnormal inputs to zero")?;
+    Ok(params.iter().map(|v| v.flush_subnormals()).collect())
+  } else {
+    Ok(params.to_vec())
+  }
+}
+
+/// Converts a result that was computed in the input format to the output format, if they differ.
+/// `step` is the number of the conversion step in the trace.
+pub fn to_output_type(f: &mut dyn Write, env: &FloatingPointEnv, step: usize, value: &Float, exception: Exception, output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+  if value.params() == output_type {
+    return Ok((value.clone().into(), exception));
+  }
+  writeln!(f, "\n{}. Convert to destination format\n", step)?;
+  let (value, converted) = Convert.execute_visual(f, env, &[value.clone().into()], output_type)?;
+  Ok((value, exception | converted))
+}
 
-  fn execute_visual(&self, fomatter: &mut dyn Write, env: &FloatingPointEnv, params: &[Float], output_type: &FloatParameters) -> Result<(Float, Exception), std::fmt::Error>;
+/// Formats a finite float as its significand in binary times a power of two.
+pub fn format_binary_sci(v: &Float) -> String {
+  let sig = bits_to_biguint(&v.significand_logical());
+  format!("{} × 2^{}", format_binary_point(&sig, v.params().sig_bits), v.exponent_logical())
 }
 
 pub fn collect_ops() -> BTreeMap<String, Rc<dyn Op>> {
   let mut h = BTreeMap::<String, Rc<dyn Op>>::new();
   h.insert("add".into(), Rc::new(AddSub(false)));
   h.insert("sub".into(), Rc::new(AddSub(true)));
+  h.insert("mul".into(), Rc::new(Mul));
+  h.insert("div".into(), Rc::new(Div));
+  h.insert("convert".into(), Rc::new(Convert));
+  // camelCase as IEEE 754 spells scaleB and logB, with the C names as aliases
+  h.insert("scaleB".into(), Rc::new(ScaleB));
+  h.insert("ldexp".into(), Rc::new(ScaleB));
+  h.insert("logB".into(), Rc::new(LogB { int: false }));
+  h.insert("logb".into(), Rc::new(LogB { int: false }));
+  h.insert("ilogB".into(), Rc::new(LogB { int: true }));
+  h.insert("ilogb".into(), Rc::new(LogB { int: true }));
+  h.insert("frexp".into(), Rc::new(Frexp));
+  h.insert("nextup".into(), Rc::new(Next { up: true }));
+  h.insert("nextdown".into(), Rc::new(Next { up: false }));
+  h.insert("nextafter".into(), Rc::new(NextAfter));
+  h.insert("neg".into(), Rc::new(SignBit { kind: SignKind::Negate }));
+  h.insert("abs".into(), Rc::new(SignBit { kind: SignKind::Abs }));
+  h.insert("copysign".into(), Rc::new(SignBit { kind: SignKind::CopySign }));
+  // expressions can't contain '-' in a name, so these have an alias with '_'
+  h.insert("from-int".into(), Rc::new(FromInt));
+  h.insert("from_int".into(), Rc::new(FromInt));
+  let to_int = Rc::new(ToInt { ty: I32_TYPE, semantics: IntSemantics::default() });
+  h.insert("to-int".into(), to_int.clone());
+  h.insert("to_int".into(), to_int);
+  let rounding = [
+    ("round", Some(RoundingMode::TiesToAway)),
+    ("roundeven", Some(RoundingMode::TiesToEven)),
+    ("floor", Some(RoundingMode::TowardNegative)),
+    ("ceil", Some(RoundingMode::TowardPositive)),
+    ("trunc", Some(RoundingMode::TowardZero)),
+  ];
+  for (name, mode) in rounding {
+    h.insert(name.into(), Rc::new(RoundToIntegral { mode, exact: false }));
+  }
+  h.insert("rint".into(), Rc::new(RoundToIntegral { mode: None, exact: true }));
+  h.insert("totalOrder".into(), Rc::new(TotalOrder));
+  let predicates = [
+    (Predicate::Equal, "Equal"),
+    (Predicate::NotEqual, "NotEqual"),
+    (Predicate::Greater, "Greater"),
+    (Predicate::GreaterEqual, "GreaterEqual"),
+    (Predicate::Less, "Less"),
+    (Predicate::LessEqual, "LessEqual"),
+  ];
+  for (predicate, name) in predicates {
+    h.insert(format!("compareQuiet{}", name), Rc::new(Compare { predicate, signaling: false }));
+    h.insert(format!("compareSignaling{}", name), Rc::new(Compare { predicate, signaling: true }));
+  }
+  h.insert("compareQuietUnordered".into(), Rc::new(Compare { predicate: Predicate::Unordered, signaling: false }));
+  h.insert("compareQuietOrdered".into(), Rc::new(Compare { predicate: Predicate::Ordered, signaling: false }));
+  let min_max = [
+    (MinMaxKind::Propagate, "minimum", "maximum"),
+    (MinMaxKind::Number, "minimumNumber", "maximumNumber"),
+    (MinMaxKind::Legacy, "minNum", "maxNum"),
+  ];
+  for (kind, min, max) in min_max {
+    h.insert(min.into(), Rc::new(MinMax { kind, max: false }));
+    h.insert(max.into(), Rc::new(MinMax { kind, max: true }));
+  }
   h
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+use std::fmt::Write;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{bits_to_biguint, pow2, Float, FloatParameters};
+use crate::ops::{floats, float_result, flush_inputs, format_binary_sci, propagate_nan, Exception, Op, Value};
+use crate::str_conv::format_binary_point;
+
+pub struct Mul;
+
+impl Op for Mul {
+  fn num_params(&self) -> usize {
+    2
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    writeln!(f, "\n1. Classify inputs\n")?;
+    let params = flush_inputs(f, env, params)?;
+    if let Some(ret) = propagate_nan(f, &params, output_type)? {
+      return Ok(ret);
+    }
+    let a = &params[0];
+    let b = &params[1];
+    let a_class = a.classify();
+    let b_class = b.classify();
+    let sign = a.sign() ^ b.sign();
+
+    if (a_class.inf() && b_class.zero()) || (a_class.zero() && b_class.inf()) {
+      writeln!(f, "- Operation is Infinity * 0, return NaN")?;
+      return Ok((Float::nan(output_type).into(), Exception::INVALID_OPERATION));
+    }
+    if a_class.inf() || b_class.inf() {
+      writeln!(f, "- Operation is Infinity * Nonzero, return {}Infinity", if sign { "-" } else { "" })?;
+      return Ok((Float::inf(output_type, sign).into(), Exception::default()));
+    }
+    writeln!(f, "- Both inputs are finite")?;
+
+    writeln!(f, "\n2. Multiply significands and add exponents\n")?;
+    writeln!(f, "- The sign of the result is {} xor {} = {}", a.sign() as u8, b.sign() as u8, sign as u8)?;
+    let a_sig = bits_to_biguint(&a.significand_logical());
+    let b_sig = bits_to_biguint(&b.significand_logical());
+    let frac_bits = a.params().sig_bits + b.params().sig_bits;
+    let exp = a.exponent_logical() + b.exponent_logical();
+    let product = &a_sig * &b_sig;
+    writeln!(f, "  {}", format_binary_sci(a))?;
+    writeln!(f, "* {}", format_binary_sci(b))?;
+    writeln!(f, "= {} × 2^{}", format_binary_point(&product, frac_bits), exp)?;
+
+    writeln!(f, "\n3. Round to destination format.\n")?;
+    let value = BigRational::from_integer(BigInt::from(product)) * pow2(exp - frac_bits as i64);
+    env.round(f, sign, &value, output_type).map(float_result)
+  }
+}
+
@@ -0,0 +1,128 @@
+use std::fmt::Write;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{Float, FloatParameters};
+use crate::ops::{floats, propagate_nan, to_output_type, Exception, Op, Value};
+use crate::printers::binary::print_float;
+
+/// Shows the encodings before and after a step, with the bits that changed highlighted.
+pub fn show_step(f: &mut dyn Write, from: &Float, to: &Float) -> std::fmt::Result {
+  let diff = from.bits().to_bitvec() ^ to.bits();
+  let mut before = String::new();
+  let mut after = String::new();
+  print_float(&mut before, from, Some(&diff))?;
+  print_float(&mut after, to, Some(&diff))?;
+  writeln!(f, "  {}", before)?;
+  writeln!(f, "→ {}", after)
+}
+
+/// Explains the step from a float that isn't NaN to its neighbour `to`.
+fn explain_step(f: &mut dyn Write, from: &Float, to: &Float) -> std::fmt::Result {
+  let from_class = from.classify();
+  let to_class = to.classify();
+  if from_class.inf() {
+    writeln!(f, "- Stepping back from infinity gives the finite float with the largest magnitude")?;
+    return show_step(f, from, to);
+  }
+  if from_class.zero() {
+    writeln!(f, "- Zero has no magnitude to shrink, so the neighbour is the smallest subnormal with the sign of the direction")?;
+    return show_step(f, from, to);
+  }
+
+  // the exponent and significand fields together count the floats from zero
+  let grows = to.ordered_integer().magnitude() > from.ordered_integer().magnitude();
+  if grows {
+    writeln!(f, "- The magnitude grows, so 1 is added to the exponent and significand fields read as one integer")?;
+    if from.significand_bits().all() {
+      writeln!(f, "- The significand field is all ones, the carry ripples into the exponent field: {} → {}", from.exponent_bits_integer(), to.exponent_bits_integer())?;
+    }
+  } else {
+    writeln!(f, "- The magnitude shrinks, so 1 is subtracted from the exponent and significand fields read as one integer")?;
+    if from.significand_bits().not_any() {
+      writeln!(f, "- The significand field is all zeros, the borrow comes from the exponent field: {} → {}", from.exponent_bits_integer(), to.exponent_bits_integer())?;
+    }
+  }
+  show_step(f, from, to)?;
+
+  if to_class.inf() {
+    writeln!(f, "- The exponent field is now all ones with a zero significand, the result is infinity")?;
+  } else if to_class.zero() {
+    writeln!(f, "- The magnitude is now zero, the result keeps the sign of the input: {}0", if to.sign() { "-" } else { "+" })?;
+  } else if from_class.normal() && to_class.subnormal() {
+    writeln!(f, "- The exponent field is now 0, the result is subnormal")?;
+  } else if from_class.subnormal() && to_class.normal() {
+    writeln!(f, "- The exponent field is now 1, the result is normal")?;
+  }
+  Ok(())
+}
+
+/// `nextUp(A)` and `nextDown(A)`, the neighbouring float above or below A.
+pub struct Next {
+  pub up: bool,
+}
+
+impl Op for Next {
+  fn num_params(&self) -> usize {
+    1
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+    writeln!(f, "\n1. Classify input\n")?;
+    if let Some(ret) = propagate_nan(f, params, output_type)? {
+      return Ok(ret);
+    }
+    let class = a.classify();
+    if class.inf() && a.sign() != self.up {
+      writeln!(f, "- Input is {}Infinity, there is nothing {} it, return it", if self.up { "+" } else { "-" }, if self.up { "above" } else { "below" })?;
+      return to_output_type(f, env, 2, a, Exception::default(), output_type);
+    }
+    writeln!(f, "- Input is not NaN")?;
+
+    writeln!(f, "\n2. Step to the {} neighbour\n", if self.up { "upper" } else { "lower" })?;
+    let next = if self.up { a.next_up() } else { a.next_down() };
+    explain_step(f, a, &next)?;
+    writeln!(f, "- nextUp and nextDown are exact and signal no exceptions")?;
+    to_output_type(f, env, 3, &next, Exception::default(), output_type)
+  }
+}
+
+/// `nextafter(A, B)`, the neighbour of A in the direction of B, as in C.
+pub struct NextAfter;
+
+impl Op for NextAfter {
+  fn num_params(&self) -> usize {
+    2
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+    let b = &params[1];
+    writeln!(f, "\n1. Classify inputs\n")?;
+    if let Some(ret) = propagate_nan(f, params, output_type)? {
+      return Ok(ret);
+    }
+    let next = a.next_after(b);
+    if a.compare(b).unwrap().is_eq() {
+      writeln!(f, "- A equals B, return A with the sign of B")?;
+      return to_output_type(f, env, 2, &next, Exception::default(), output_type);
+    }
+    let up = a.compare(b).unwrap().is_lt();
+    writeln!(f, "- A {} B, so step {}", if up { "<" } else { ">" }, if up { "up" } else { "down" })?;
+
+    writeln!(f, "\n2. Step to the {} neighbour\n", if up { "upper" } else { "lower" })?;
+    explain_step(f, a, &next)?;
+    // unlike nextUp and nextDown, C signals stepping out of the normal range
+    let mut exception = Exception::default();
+    let class = next.classify();
+    if class.inf() {
+      writeln!(f, "- C's nextafter signals overflow and inexact when the result is infinite")?;
+      exception = Exception::OVERFLOW | Exception::INEXACT;
+    } else if class.subnormal() || class.zero() {
+      writeln!(f, "- C's nextafter signals underflow and inexact when the result is subnormal or zero")?;
+      exception = Exception::UNDERFLOW | Exception::INEXACT;
+    }
+    to_output_type(f, env, 3, &next, exception, output_type)
+  }
+}
@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+use std::fmt::Write;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use crate::fenv::{FloatingPointEnv, RoundingMode};
+use crate::floats::{bits_to_biguint, Float, FloatParameters};
+use crate::ops::{floats, flush_inputs, propagate_nan, to_output_type, Exception, Op, Value};
+use crate::str_conv::format_binary_point;
+
+/// Splits the magnitude of a finite float at the binary point, showing where it falls in the significand.
+/// Returns the integer part, the fraction below the binary point and the number of bits in the fraction.
+pub fn split_at_binary_point(f: &mut dyn Write, a: &Float) -> Result<(BigUint, BigUint, usize), std::fmt::Error> {
+  let p = a.params().sig_bits as i64;
+  let exp = a.exponent_logical();
+  let sig = bits_to_biguint(&a.significand_logical());
+  // the number of significand bits below the binary point
+  let frac_bits = p - exp;
+  if frac_bits <= 0 {
+    writeln!(f, "- The exponent is {}, so the binary point falls at or after the last of the {} fraction bits", exp, p)?;
+    return Ok((sig << -frac_bits, BigUint::zero(), 0));
+  }
+  let frac_bits = frac_bits as usize;
+  writeln!(f, "- The exponent is {}, so the binary point falls {} bits into the significand", exp, frac_bits)?;
+  writeln!(f, "  {}", format_binary_point(&sig, frac_bits))?;
+  let (int, frac) = sig.div_rem(&(BigUint::one() << frac_bits));
+  Ok((int, frac, frac_bits))
+}
+
+/// Rounds a float to an integral value in the same format, as the IEEE 754 `roundToIntegral` operations.
+/// Without a `mode` the rounding mode of the environment is used.
+/// `exact` signals inexact when the value changes, as `roundToIntegralExact`.
+pub struct RoundToIntegral {
+  pub mode: Option<RoundingMode>,
+  pub exact: bool,
+}
+
+impl Op for RoundToIntegral {
+  fn num_params(&self) -> usize {
+    1
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    writeln!(f, "\n1. Classify input\n")?;
+    let params = flush_inputs(f, env, params)?;
+    if let Some(ret) = propagate_nan(f, &params, output_type)? {
+      return Ok(ret);
+    }
+    let a = &params[0];
+    let class = a.classify();
+    if class.inf() || class.zero() {
+      writeln!(f, "- Input is {}, which is already integral", if class.inf() { "infinite" } else { "zero" })?;
+      return to_output_type(f, env, 2, a, Exception::default(), output_type);
+    }
+    writeln!(f, "- Input is finite")?;
+
+    writeln!(f, "\n2. Discard the fractional bits\n")?;
+    let (int, frac, frac_bits) = split_at_binary_point(f, a)?;
+    if frac_bits == 0 {
+      writeln!(f, "- Every float this large is an integer, return the input")?;
+      return to_output_type(f, env, 3, a, Exception::default(), output_type);
+    }
+    let inexact = !frac.is_zero();
+    let vs_half = (&frac << 1u8).cmp(&(BigUint::one() << frac_bits));
+    let frac_desc = match (inexact, vs_half) {
+      (false, _) => "zero",
+      (true, Ordering::Less) => "less than one half",
+      (true, Ordering::Equal) => "exactly one half",
+      (true, Ordering::Greater) => "more than one half",
+    };
+    writeln!(f, "- The integer part is {}, the discarded fraction {} is {}", int, format_binary_point(&frac, frac_bits), frac_desc)?;
+
+    writeln!(f, "\n3. Round\n")?;
+    let mode = self.mode.unwrap_or(env.rounding_mode);
+    writeln!(f, "- The rounding mode is {:?}", mode)?;
+    let mut int = int;
+    if !inexact {
+      writeln!(f, "- Nothing was discarded, the input is already integral")?;
+    } else if mode.rounds_up(a.sign(), int.is_odd(), vs_half, inexact) {
+      int += 1u32;
+      writeln!(f, "- Rounding the magnitude up to {}", int)?;
+    } else {
+      writeln!(f, "- Rounding the magnitude down to {}", int)?;
+    }
+    if int.is_zero() {
+      writeln!(f, "- The result is zero, it keeps the sign of the input: {}0", if a.sign() { "-" } else { "+" })?;
+    }
+    let mut exception = Exception::default();
+    if inexact && self.exact {
+      writeln!(f, "- The value changed, signal inexact")?;
+      exception = Exception::INEXACT;
+    }
+    // rounding up can reach the next power of two at most, which the format holds exactly
+    let (value, rounded) = FloatingPointEnv::default().round(&mut String::new(), a.sign(), &BigRational::from_integer(int.into()), a.params())?;
+    to_output_type(f, env, 4, &value, exception | rounded, output_type)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::floats::F32_PARAMS;
+  use crate::ops::collect_ops;
+
+  fn apply(name: &str, a: &str) -> (Float, Exception) {
+    let a = Float::parse(a, &F32_PARAMS).unwrap();
+    let (value, exception) = collect_ops()[name].execute_visual(&mut String::new(), &FloatingPointEnv::default(), &[a.into()], &F32_PARAMS).unwrap();
+    (value.float().unwrap().clone(), exception)
+  }
+
+  fn rounds_to(name: &str, a: &str, expected: &str) -> bool {
+    let (value, _) = apply(name, a);
+    let expected = Float::parse(expected, &F32_PARAMS).unwrap();
+    value.sign() == expected.sign() && value.exact_signed_value() == expected.exact_signed_value()
+  }
+
+  #[test]
+  fn each_mode() {
+    let cases = [
+      ("round", ["3", "-3", "3", "-4", "4"]),
+      ("roundeven", ["2", "-2", "3", "-4", "4"]),
+      ("floor", ["2", "-3", "2", "-4", "3"]),
+      ("ceil", ["3", "-2", "3", "-3", "4"]),
+      ("trunc", ["2", "-2", "2", "-3", "3"]),
+    ];
+    for (name, expected) in cases {
+      for (a, e) in ["2.5", "-2.5", "2.75", "-3.5", "3.5"].iter().zip(expected) {
+        assert!(rounds_to(name, a, e), "{}({}) should be {}", name, a, e);
+      }
+    }
+  }
+
+  #[test]
+  fn keeps_sign_and_specials() {
+    assert!(rounds_to("trunc", "-0.5", "-0"));
+    assert!(rounds_to("ceil", "-0.25", "-0"));
+    assert!(rounds_to("floor", "0.25", "0"));
+    assert!(rounds_to("round", "0.5", "1"));
+    assert!(rounds_to("floor", "-inf", "-inf"));
+    assert!(rounds_to("round", "16777216", "16777216"));
+    assert!(apply("round", "nan").0.classify().nan());
+  }
+
+  #[test]
+  fn only_rint_signals_inexact() {
+    assert_eq!(apply("round", "2.5").1, Exception::default());
+    assert_eq!(apply("rint", "2.5").1, Exception::INEXACT);
+    assert!(rounds_to("rint", "2.5", "2"));
+    assert_eq!(apply("rint", "2").1, Exception::default());
+  }
+}
@@ -0,0 +1,120 @@
+use std::cmp::Ordering;
+use std::fmt::Write;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{Float, FloatParameters};
+use crate::ops::add::AddSub;
+use crate::ops::next::show_step;
+use crate::ops::{floats, to_output_type, Exception, Op, Value};
+use crate::printers::binary::print_float;
+
+/// The sign-bit operations, which IEEE 754 defines as quiet bit operations.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SignKind {
+  /// `negate(A)`, flips the sign bit.
+  Negate,
+  /// `abs(A)`, clears the sign bit.
+  Abs,
+  /// `copySign(A, B)`, copies the sign bit of B.
+  CopySign,
+}
+
+/// Changes only the sign bit of A, without rounding or signalling exceptions.
+pub struct SignBit {
+  pub kind: SignKind,
+}
+
+impl SignBit {
+  /// Returns the arithmetic that is commonly used instead of the bit operation, and whether it subtracts A from 0.
+  /// The comparisons are C's `<`, which signals invalid operation on NaN.
+  fn arithmetic(&self, params: &[Float]) -> (&'static str, bool, Exception) {
+    let is_negative = |v: &Float| v.compare(&Float::zero(v.params())) == Some(Ordering::Less);
+    let compared = match self.kind {
+      SignKind::Negate => return ("0 - A", true, Exception::default()),
+      SignKind::Abs => &params[..1],
+      SignKind::CopySign => &params[..2],
+    };
+    let exception = if compared.iter().any(|v| v.classify().nan()) { Exception::INVALID_OPERATION } else { Exception::default() };
+    match self.kind {
+      SignKind::Abs => ("A < 0 ? 0 - A : A", is_negative(&params[0]), exception),
+      _ => ("(A < 0) != (B < 0) ? 0 - A : A", is_negative(&params[0]) != is_negative(&params[1]), exception),
+    }
+  }
+
+  /// Shows what the arithmetic version of the operation gives, and why it differs from the bit operation.
+  fn compare_arithmetic(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Float], result: &Float) -> std::fmt::Result {
+    let a = &params[0];
+    let (expr, subtract, compare_exception) = self.arithmetic(params);
+    let (value, sub_exception) = if subtract {
+      AddSub(true).execute(env, &[Float::zero(a.params()).into(), a.clone().into()], a.params())
+    } else {
+      (a.clone().into(), Exception::default())
+    };
+    let value = value.float().expect("subtraction gives a float").clone();
+    let mut bits = String::new();
+    print_float(&mut bits, &value, None)?;
+    writeln!(f, "- The arithmetic version {} gives", expr)?;
+    writeln!(f, "  {}", bits)?;
+
+    let exception = compare_exception | sub_exception;
+    if value.bits() == result.bits() && exception == Exception::default() {
+      return writeln!(f, "- For this input it agrees with the bit operation");
+    }
+    if compare_exception != Exception::default() {
+      writeln!(f, "- Comparing NaN with < signals invalid operation")?;
+    }
+    if subtract && a.classify().nan() {
+      if sub_exception.contains(Exception::INVALID_OPERATION) {
+        writeln!(f, "- A is a signalling NaN, subtracting it returns a quiet NaN and signals invalid operation")?;
+      } else {
+        writeln!(f, "- Subtracting a NaN returns a NaN, but its sign and payload aren't specified")?;
+      }
+    } else if subtract && a.classify().zero() && value.bits() != result.bits() {
+      writeln!(f, "- 0 - 0 is exactly zero, which is +0 in every rounding mode except toward negative, so the sign isn't flipped")?;
+    } else if subtract && env.flush_subnormals_to_zero && a.classify().subnormal() {
+      writeln!(f, "- The environment flushes subnormals to zero, so the subtraction loses A")?;
+    } else if value.bits() != result.bits() {
+      let names = if self.kind == SignKind::Abs { "A" } else { "A or B" };
+      writeln!(f, "- x < 0 is false for -0 and NaN, so when {} is one of them the comparison misses the sign bit", names)?;
+    }
+    Ok(())
+  }
+}
+
+impl Op for SignBit {
+  fn num_params(&self) -> usize {
+    if self.kind == SignKind::CopySign { 2 } else { 1 }
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, env: &FloatingPointEnv, params: &[Value], output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+    writeln!(f, "\n1. Set the sign bit\n")?;
+    writeln!(f, "- This is a quiet bit operation: nothing is rounded and no exception is signalled, even for a signalling NaN")?;
+    let sign = match self.kind {
+      SignKind::Negate => {
+        writeln!(f, "- The sign bit of A is flipped")?;
+        !a.sign()
+      }
+      SignKind::Abs => {
+        writeln!(f, "- The sign bit of A is cleared")?;
+        false
+      }
+      SignKind::CopySign => {
+        let b = &params[1];
+        writeln!(f, "- The sign bit of B is {}, it is copied to A", b.sign() as u8)?;
+        b.sign()
+      }
+    };
+    let mut result = a.clone();
+    result.set_sign(sign);
+    if sign == a.sign() {
+      writeln!(f, "- The sign bit already has that value, A is returned unchanged")?;
+    } else {
+      show_step(f, a, &result)?;
+    }
+
+    writeln!(f, "\n2. Compare with arithmetic\n")?;
+    self.compare_arithmetic(f, env, params, &result)?;
+    to_output_type(f, env, 3, &result, Exception::default(), output_type)
+  }
+}
@@ -0,0 +1,269 @@
+use std::fmt::Write;
+use std::rc::Rc;
+use clap::ValueEnum;
+use num_bigint::BigInt;
+use num_traits::Zero;
+use crate::fenv::FloatingPointEnv;
+use crate::floats::FloatParameters;
+use crate::ints::{IntType, Integer, I32_TYPE};
+use crate::ops::round::split_at_binary_point;
+use crate::ops::{floats, Exception, Op, Value};
+use crate::str_conv::format_binary_point;
+
+/// How a language or instruction set handles float to integer conversions that don't fit.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum IntSemantics {
+  /// Rust `as`: saturates to the nearest bound, NaN becomes 0.
+  #[default]
+  Rust,
+  /// C and C++ casts: undefined behaviour.
+  C,
+  /// x86 `cvttsd2si`: returns the "integer indefinite" value.
+  X86,
+  /// ARM `fcvtzs`/`fcvtzu`: saturates, NaN becomes 0.
+  Arm,
+  /// Java casts: saturates to `int` or `long`, narrower types then keep the low bits. Java never traps here.
+  Java,
+  /// WebAssembly `trunc`: traps.
+  Wasm,
+}
+
+/// Converts a float to an integer type, truncating toward zero.
+pub struct ToInt {
+  pub ty: IntType,
+  pub semantics: IntSemantics,
+}
+
+impl ToInt {
+  /// The type whose range decides whether the conversion is invalid.
+  fn checked_type(&self) -> IntType {
+    if self.semantics == IntSemantics::Java && self.ty.bits < 32 {
+      I32_TYPE
+    } else {
+      self.ty
+    }
+  }
+
+  /// Saturates to the bounds of `ty`, with NaN mapped to 0.
+  fn saturate(ty: &IntType, value: Option<&BigInt>) -> BigInt {
+    match value {
+      None => BigInt::zero(),
+      Some(v) if v < &ty.min() => ty.min(),
+      Some(v) if v > &ty.max() => ty.max(),
+      Some(v) => v.clone(),
+    }
+  }
+
+  /// Explains what the semantics do with an input that is NaN (`value` is `None`) or out of range.
+  fn out_of_range(&self, f: &mut dyn Write, value: Option<&BigInt>) -> Result<Value, std::fmt::Error> {
+    let ty = &self.ty;
+    let result = match self.semantics {
+      IntSemantics::Rust | IntSemantics::Arm => {
+        let result = Self::saturate(ty, value);
+        if value.is_none() {
+          writeln!(f, "- {:?} converts NaN to 0", self.semantics)?;
+        } else {
+          writeln!(f, "- {:?} saturates to the nearest bound, {}", self.semantics, result)?;
+        }
+        result
+      }
+      IntSemantics::X86 => {
+        // the unsigned form is the AVX-512 vcvttsd2usi, which returns all ones
+        let result = if ty.signed { ty.min() } else { ty.max() };
+        writeln!(f, "- x86 returns the \"integer indefinite\" value {} (0x{:x}), whatever the input", result, ty.encode(&result))?;
+        if ty.bits != 32 && ty.bits != 64 {
+          writeln!(f, "- The instructions only exist for 32 and 64 bit integers, the same pattern is assumed for {}", ty)?;
+        }
+        result
+      }
+      IntSemantics::Java => return self.java(f, value),
+      IntSemantics::C => {
+        writeln!(f, "- In C and C++ this is undefined behaviour, the compiler may assume it never happens")?;
+        writeln!(f, "- In practice x86 gives the \"integer indefinite\" value and ARM saturates, optimised code may do anything")?;
+        return Ok(Value::Undefined("undefined behaviour in C and C++".to_owned()));
+      }
+      IntSemantics::Wasm => {
+        let reason = if value.is_none() { "invalid conversion to integer" } else { "integer overflow" };
+        writeln!(f, "- WebAssembly traps with \"{}\", the saturating trunc_sat instructions give the Rust result", reason)?;
+        return Ok(Value::Undefined(format!("WebAssembly trap: {}", reason)));
+      }
+    };
+    Ok(Value::Int(Integer { value: result, ty: *ty }))
+  }
+
+  /// Java converts to `int` (or `long`) first, saturating, and narrower types keep the low bits of that.
+  fn java(&self, f: &mut dyn Write, value: Option<&BigInt>) -> Result<Value, std::fmt::Error> {
+    let ty = &self.ty;
+    let intermediate = self.checked_type();
+    let saturated = Self::saturate(&intermediate, value);
+    match value {
+      None => writeln!(f, "- Java converts NaN to 0")?,
+      Some(v) if !intermediate.contains(v) => writeln!(f, "- Java saturates to the nearest bound of {}, {}", intermediate, saturated)?,
+      Some(_) => {}
+    }
+    if intermediate == *ty {
+      return Ok(Value::Int(Integer { value: saturated, ty: *ty }));
+    }
+    let result = ty.wrap(&saturated);
+    writeln!(f, "- Then the low {} bits are kept: {} becomes {}", ty.bits, saturated, result)?;
+    Ok(Value::Int(Integer { value: result, ty: *ty }))
+  }
+}
+
+impl Op for ToInt {
+  fn num_params(&self) -> usize {
+    1
+  }
+
+  fn settings(&self) -> &[&'static str] {
+    &["TYPE", "SEMANTICS"]
+  }
+
+  /// Each setting is either an integer type such as `u8`, or the name of the semantics, in any order.
+  fn with_settings(&self, settings: &[String]) -> Result<Rc<dyn Op>, String> {
+    let mut op = ToInt { ty: self.ty, semantics: self.semantics };
+    for setting in settings {
+      if let Ok(ty) = IntType::parse(setting) {
+        op.ty = ty;
+      } else if let Ok(semantics) = IntSemantics::from_str(setting, true) {
+        op.semantics = semantics;
+      } else {
+        let names = IntSemantics::value_variants().iter()
+          .filter_map(|v| v.to_possible_value())
+          .map(|v| v.get_name().to_owned())
+          .collect::<Vec<_>>();
+        return Err(format!("\"{}\" is neither an integer type such as i32 or u8, nor one of the semantics {}", setting, names.join(", ")));
+      }
+    }
+    Ok(Rc::new(op))
+  }
+
+  fn execute_visual(&self, f: &mut dyn Write, _env: &FloatingPointEnv, params: &[Value], _output_type: &FloatParameters) -> Result<(Value, Exception), std::fmt::Error> {
+    let params = &floats(params);
+    let a = &params[0];
+    let ty = &self.ty;
+    writeln!(f, "\n1. Classify input\n")?;
+    writeln!(f, "- Converting to {} ({} to {}) with {:?} semantics", ty, ty.min(), ty.max(), self.semantics)?;
+    let class = a.classify();
+    if class.nan() {
+      writeln!(f, "- Input is NaN, which has no integer value, signal invalid operation")?;
+      return Ok((self.out_of_range(f, None)?, Exception::INVALID_OPERATION));
+    }
+    if class.inf() {
+      writeln!(f, "- Input is infinite, which is out of range, signal invalid operation")?;
+      let bound = if a.sign() { ty.min() - 1 } else { ty.max() + 1 };
+      return Ok((self.out_of_range(f, Some(&bound))?, Exception::INVALID_OPERATION));
+    }
+    writeln!(f, "- Input is finite")?;
+
+    writeln!(f, "\n2. Truncate toward zero\n")?;
+    let (int, frac, frac_bits) = if class.zero() {
+      writeln!(f, "- Input is zero")?;
+      Default::default()
+    } else {
+      split_at_binary_point(f, a)?
+    };
+    let inexact = !frac.is_zero();
+    if inexact {
+      writeln!(f, "- Discarding the fraction {}", format_binary_point(&frac, frac_bits))?;
+    }
+    let value = if a.sign() { -BigInt::from(int) } else { BigInt::from(int) };
+    writeln!(f, "- The truncated value is {}", value)?;
+
+    writeln!(f, "\n3. Check the range\n")?;
+    let checked = self.checked_type();
+    if checked != *ty {
+      writeln!(f, "- Java converts to {} first, so its range is checked", checked)?;
+    }
+    if !checked.contains(&value) {
+      writeln!(f, "- {} is out of range for {}, signal invalid operation", value, checked)?;
+      return Ok((self.out_of_range(f, Some(&value))?, Exception::INVALID_OPERATION));
+    }
+    writeln!(f, "- {} fits in {}", value, checked)?;
+    let mut exception = Exception::default();
+    if inexact {
+      writeln!(f, "- A nonzero fraction was discarded, signal inexact")?;
+      exception = Exception::INEXACT;
+    }
+    let result = if self.semantics == IntSemantics::Java { self.java(f, Some(&value))? } else { Value::Int(Integer { value, ty: *ty }) };
+    Ok((result, exception))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::floats::{Float, F64_PARAMS};
+
+  fn to_int(a: &str, ty: &str, semantics: IntSemantics) -> (Value, Exception) {
+    let a = Float::parse(a, &F64_PARAMS).unwrap();
+    let op = ToInt { ty: IntType::parse(ty).unwrap(), semantics };
+    op.execute_visual(&mut String::new(), &FloatingPointEnv::default(), &[a.into()], &F64_PARAMS).unwrap()
+  }
+
+  fn int(a: &str, ty: &str, semantics: IntSemantics) -> i64 {
+    match to_int(a, ty, semantics).0 {
+      Value::Int(v) => v.value.try_into().unwrap(),
+      v => panic!("expected an integer, got {:?}", v),
+    }
+  }
+
+  #[test]
+  fn truncates_in_range() {
+    assert_eq!(to_int("2.75", "i32", IntSemantics::Rust).1, Exception::INEXACT);
+    assert_eq!(int("2.75", "i32", IntSemantics::Rust), 2);
+    assert_eq!(int("-2.75", "i32", IntSemantics::Rust), -2);
+    assert_eq!(int("-0.5", "u8", IntSemantics::Rust), 0);
+    assert_eq!(to_int("-0", "u8", IntSemantics::Rust).1, Exception::default());
+    assert_eq!(int("1e10", "i64", IntSemantics::X86), 10_000_000_000);
+  }
+
+  #[test]
+  fn rust_and_arm_saturate() {
+    for semantics in [IntSemantics::Rust, IntSemantics::Arm] {
+      assert_eq!(to_int("300", "u8", semantics).1, Exception::INVALID_OPERATION);
+      assert_eq!(int("300", "u8", semantics), 255);
+      assert_eq!(int("-1", "u8", semantics), 0);
+      assert_eq!(int("-inf", "i32", semantics), i32::MIN as i64);
+      assert_eq!(int("nan", "i32", semantics), 0);
+    }
+  }
+
+  #[test]
+  fn x86_returns_integer_indefinite() {
+    assert_eq!(int("3e9", "i32", IntSemantics::X86), i32::MIN as i64);
+    assert_eq!(int("-3e9", "i32", IntSemantics::X86), i32::MIN as i64);
+    assert_eq!(int("nan", "i64", IntSemantics::X86), i64::MIN);
+    assert_eq!(int("-1", "u32", IntSemantics::X86), u32::MAX as i64);
+  }
+
+  #[test]
+  fn java_saturates_then_wraps() {
+    assert_eq!(int("300", "i8", IntSemantics::Java), 44);
+    assert_eq!(int("1e10", "i16", IntSemantics::Java), -1);
+    assert_eq!(int("1e10", "i32", IntSemantics::Java), i32::MAX as i64);
+    assert_eq!(int("nan", "i8", IntSemantics::Java), 0);
+    // within the range of int the conversion is exact, so only the wrap is visible
+    assert_eq!(to_int("300", "i8", IntSemantics::Java).1, Exception::default());
+  }
+
+  #[test]
+  fn c_and_wasm_have_no_result() {
+    let (value, exception) = to_int("1e10", "i32", IntSemantics::C);
+    assert!(matches!(value, Value::Undefined(_)));
+    assert_eq!(exception, Exception::INVALID_OPERATION);
+    assert!(matches!(to_int("1e10", "i32", IntSemantics::Wasm).0, Value::Undefined(s) if s.contains("integer overflow")));
+    assert!(matches!(to_int("nan", "i32", IntSemantics::Wasm).0, Value::Undefined(s) if s.contains("invalid conversion")));
+    assert_eq!(int("2.5", "i32", IntSemantics::Wasm), 2);
+  }
+
+  #[test]
+  fn settings_in_any_order() {
+    let op = ToInt { ty: I32_TYPE, semantics: IntSemantics::Rust };
+    let a = Value::from(Float::parse("300", &F64_PARAMS).unwrap());
+    let settings = ["java".to_owned(), "i8".to_owned()];
+    let (value, _) = op.with_settings(&settings).unwrap().execute(&FloatingPointEnv::default(), &[a], &F64_PARAMS);
+    assert_eq!(value.int().unwrap().value, BigInt::from(44));
+    assert!(op.with_settings(&["f32".to_owned()]).is_err());
+  }
+}
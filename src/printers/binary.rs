@@ -1,25 +1,47 @@
-use std::fmt::{Pointer, Write};
-use crate::floats::{Float, FloatParameters};
-use crate::printers::{bit2char, int_length, print_bitset, Printer, CYAN, DARK_CYAN, DARK_GREEN, DARK_YELLOW, GREEN, RESET, YELLOW};
+use std::fmt::Write;
+use crate::floats::{BitSlice, Float, FloatParameters};
+use crate::printers::{bit2char, field_separator, int_length, print_bitset, Color, Printer, CYAN, DARK_CYAN, DARK_GREEN, DARK_YELLOW, GREEN, RED, RESET, YELLOW};
 
-fn print_float(f: &mut dyn Write, val: &Float) -> std::fmt::Result {
-  let msb_idx_size = int_length(val.params().total_length() - 1);
+/// Prints the bits of a float, most significant first, with each field in its own color.
+/// Bits set in `highlight` are printed in red instead.
+pub fn print_float(f: &mut dyn Write, val: &Float, highlight: Option<&BitSlice>) -> std::fmt::Result {
+  let params = val.params();
+  let msb_idx_size = int_length(params.total_length() - 1);
   f.write_str(&" ".repeat(msb_idx_size - 1))?;
-  f.write_str(CYAN)?;
-  f.write_char(bit2char(val.sign()))?;
-  f.write_char(' ')?;
-  f.write_str(GREEN)?;
-  print_bitset(f, val.exponent_bits())?;
-  f.write_char(' ')?;
-  f.write_str(YELLOW)?;
-  print_bitset(f, val.significand_bits())?;
-  f.write_str(RESET)?;
+  let Some(highlight) = highlight else {
+    write!(f, "{}", CYAN)?;
+    f.write_char(bit2char(val.sign()))?;
+    f.write_char(field_separator())?;
+    write!(f, "{}", GREEN)?;
+    print_bitset(f, val.exponent_bits())?;
+    f.write_char(field_separator())?;
+    write!(f, "{}", YELLOW)?;
+    print_bitset(f, val.significand_bits())?;
+    write!(f, "{}", RESET)?;
+    return Ok(());
+  };
+  for i in (0..params.total_length()).rev() {
+    let color = if i == params.total_length() - 1 { CYAN } else if i >= params.sig_bits { GREEN } else { YELLOW };
+    let color = if highlight[i] { RED } else { color };
+    write!(f, "{}{}", color, bit2char(val.bits()[i]))?;
+    if i == params.sig_bits || i == params.total_length() - 1 {
+      f.write_char(field_separator())?;
+    }
+  }
+  write!(f, "{}", RESET)?;
   Ok(())
 }
 
-fn print_guide_markers(f: &mut dyn Write, params: &FloatParameters) -> std::fmt::Result {
+/// Returns the column at which [`print_float`] prints bit `i`, counting from the least significant bit.
+pub fn bit_column(params: &FloatParameters, i: usize) -> usize {
+  let from_msb = params.total_length() - 1 - i;
+  let separators = (from_msb >= 1) as usize + (from_msb > params.exp_bits) as usize;
+  int_length(params.total_length() - 1) - 1 + from_msb + separators
+}
+
+pub fn print_guide_markers(f: &mut dyn Write, params: &FloatParameters) -> std::fmt::Result {
   write!(f, "{}{}", DARK_CYAN, params.total_length() - 1)?;
-  fn print_field(f: &mut dyn Write, color: &str, upper: usize, lower: usize) -> std::fmt::Result {
+  fn print_field(f: &mut dyn Write, color: Color, upper: usize, lower: usize) -> std::fmt::Result {
     let spaces = (upper - lower + 1).saturating_sub(int_length(upper)).saturating_sub(int_length(lower));
     if spaces != 0 {
       write!(f, " {}{}{}{}", color, upper, " ".repeat(spaces), lower)
@@ -30,28 +52,10 @@ fn print_guide_markers(f: &mut dyn Write, params: &FloatParameters) -> std::fmt:
   }
   print_field(f, DARK_GREEN, params.total_length() - 2, params.sig_bits)?;
   print_field(f, DARK_YELLOW, params.sig_bits - 1, 0)?;
-  f.write_str(RESET)?;
+  write!(f, "{}", RESET)?;
   Ok(())
 }
 
-pub struct BinaryPrinter;
-
-impl Printer for BinaryPrinter {
-  fn name(&self) -> &str {
-    "Binary"
-  }
-
-  fn description(&self) -> &str {
-    "Prints the binary representation"
-  }
-
-  fn print(&self, val: &Float) -> Vec<String> {
-    let mut s = String::new();
-    print_float(&mut s, val).unwrap();
-    vec![s]
-  }
-}
-
 pub struct BinaryPrinterWithGuide;
 
 impl Printer for BinaryPrinterWithGuide {
@@ -66,7 +70,7 @@ impl Printer for BinaryPrinterWithGuide {
   fn print(&self, val: &Float) -> Vec<String> {
     let mut s1 = String::new();
     let mut s2 = String::new();
-    print_float(&mut s1, val).unwrap();
+    print_float(&mut s1, val, None).unwrap();
     print_guide_markers(&mut s2, val.params()).unwrap();
     vec![s1, s2]
   }
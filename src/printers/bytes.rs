@@ -0,0 +1,65 @@
+use crate::floats::{bits_to_biguint, Float};
+use crate::printers::{bit2char, Color, Printer, CYAN, DARK_GRAY, GREEN, RESET, YELLOW};
+
+pub struct ByteLayoutPrinter;
+
+impl Printer for ByteLayoutPrinter {
+  fn name(&self) -> &str {
+    "Bytes"
+  }
+
+  fn description(&self) -> &str {
+    "Prints the encoding as hex and as little- and big-endian bytes, with the fields marked in each byte"
+  }
+
+  fn print(&self, val: &Float) -> Vec<String> {
+    let params = val.params();
+    let len = params.total_length();
+    let byte_count = len.div_ceil(8);
+    let value = bits_to_biguint(val.bits());
+    let mut bytes = value.to_bytes_be();
+    // leading zero bytes are dropped, and formats that aren't a whole number of bytes are padded at the top
+    while bytes.len() < byte_count {
+      bytes.insert(0, 0);
+    }
+    let hex = |bytes: &mut dyn Iterator<Item = &u8>| bytes.map(|v| format!("{:02x}", v)).collect::<Vec<_>>().join(" ");
+
+    // one character per bit, most significant first, colored by field
+    let field = |i: usize| -> (Color, char) {
+      if i >= len {
+        (DARK_GRAY, '-')
+      } else if i == len - 1 {
+        (CYAN, 's')
+      } else if i >= params.sig_bits {
+        (GREEN, 'e')
+      } else {
+        (YELLOW, 'm')
+      }
+    };
+    let mut bits = String::new();
+    let mut legend = String::new();
+    for i in (0..byte_count * 8).rev() {
+      let (color, c) = field(i);
+      let bit = i < len && val.bits()[i];
+      bits += &format!("{}{}", color, bit2char(bit));
+      legend.push(c);
+      if i.is_multiple_of(8) && i != 0 {
+        bits.push(' ');
+        legend.push(' ');
+      }
+    }
+    bits += &RESET.to_string();
+
+    let mut lines = vec![
+      format!("0x{:0width$x} ({} bits, {} bytes)", value, len, byte_count, width = len.div_ceil(4)),
+      format!("big-endian     {}", hex(&mut bytes.iter())),
+      format!("little-endian  {}", hex(&mut bytes.iter().rev())),
+      format!("bits (BE)      {}", bits),
+      format!("               {}", legend),
+    ];
+    if !len.is_multiple_of(8) {
+      lines.push(format!("the top {} bits (-) of the first big-endian byte are padding", byte_count * 8 - len));
+    }
+    lines
+  }
+}
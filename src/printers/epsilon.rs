@@ -1,23 +1,76 @@
-use crate::floats::Float;
+use num_traits::Zero;
+use crate::floats::{pow2, Float};
 use crate::printers::Printer;
+use crate::str_conv::{rational_to_exact_str, rational_to_sci_str};
 
 pub struct UnitInLastPlacePrinter;
 
+/// Formats a power of two along with its exact decimal value.
+fn format_pow2(exp: i64) -> String {
+  format!("2^{} = {}", exp, rational_to_exact_str(&pow2(exp)).unwrap())
+}
+
 impl Printer for UnitInLastPlacePrinter {
   fn name(&self) -> &str {
     "ULP"
   }
 
   fn description(&self) -> &str {
-    "Prints the units in last place (ULP)"
+    "Prints the units in last place (ULP), exactly and relative to the value"
   }
 
   fn print(&self, val: &Float) -> Vec<String> {
-    if !val.classify().finite() {
+    let class = val.classify();
+    if !class.finite() {
       return vec!["Undefined".into()];
     }
+    let params = val.params();
     let exponent = val.exponent_logical();
-    let epsilon_exp = exponent - val.params().sig_bits as i64;
-    vec![format!("2^{}", epsilon_exp)]
+    let epsilon_exp = exponent - params.sig_bits as i64;
+    let mut lines = vec![format_pow2(epsilon_exp)];
+
+    let value = val.exact_value().unwrap();
+    if !value.is_zero() {
+      let relative = pow2(epsilon_exp) / &value;
+      lines.push(format!("relative to the value: {}", rational_to_sci_str(&relative, 4)));
+    }
+
+    // subnormals and zero share the exponent of the smallest normals, so the spacing stops shrinking
+    if class.zero() {
+      lines.push("zero: the ULP is the gap to the smallest subnormal, not a fraction of the value".to_owned());
+    } else if class.subnormal() {
+      lines.push("subnormal: the spacing is fixed at the smallest subnormal, relative precision drops as the value gets smaller".to_owned());
+    }
+
+    // at a power of two the floats closer to zero are twice as dense as the ones further out,
+    // which is below the value for positive and above it for negative ones
+    let power_of_two = class.normal() && val.significand_bits().not_any() && exponent > params.min_exp();
+    if power_of_two {
+      lines.push(format!("gap away from zero: 2^{} (Goldberg ulp), gap toward zero: 2^{} (Harrison/Kahan ulp)", epsilon_exp, epsilon_exp - 1));
+    }
+    lines
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::floats::F32_PARAMS;
+
+  fn ulp(s: &str) -> Vec<String> {
+    UnitInLastPlacePrinter.print(&Float::parse(s, &F32_PARAMS).unwrap())
+  }
+
+  #[test]
+  fn gaps_at_a_power_of_two() {
+    let gaps = "gap away from zero: 2^-23 (Goldberg ulp), gap toward zero: 2^-24 (Harrison/Kahan ulp)";
+    assert_eq!(ulp("1")[2], gaps);
+    assert_eq!(ulp("-1")[2], gaps);
+    // above -1 is -(1 - 2^-24), below it -(1 + 2^-23)
+    let minus_one = Float::parse("-1", &F32_PARAMS).unwrap();
+    let value = minus_one.exact_signed_value().unwrap();
+    assert_eq!(minus_one.next_up().exact_signed_value().unwrap() - &value, pow2(-24));
+    assert_eq!(&value - minus_one.next_down().exact_signed_value().unwrap(), pow2(-23));
+    assert_eq!(ulp("1.5").len(), 2);
   }
-}
\ No newline at end of file
+}
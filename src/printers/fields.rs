@@ -0,0 +1,48 @@
+use crate::floats::{bits_to_biguint, Float, FloatClass};
+use crate::printers::{Printer, CYAN, GREEN, RESET, YELLOW};
+
+pub struct FieldsPrinter;
+
+impl Printer for FieldsPrinter {
+  fn name(&self) -> &str {
+    "Fields"
+  }
+
+  fn description(&self) -> &str {
+    "Prints the value of each field and the formula that combines them"
+  }
+
+  fn print(&self, val: &Float) -> Vec<String> {
+    let params = val.params();
+    let sign = val.sign() as u8;
+    let exp = val.exponent_bits_integer();
+    let sig = bits_to_biguint(val.significand_bits());
+    let sig_hex_width = params.sig_bits.div_ceil(4);
+    let class = val.classify();
+
+    let unbiased = if exp == 0 {
+      format!("subnormal exponent 1-bias = {}", params.min_exp())
+    } else {
+      format!("unbiased {}", exp as i64 - params.exp_bias as i64)
+    };
+    let mut lines = vec![
+      format!("sign        {}{}{} ({})", CYAN, sign, RESET, if val.sign() { "-" } else { "+" }),
+      format!("exponent    {}{}{} (0x{:x}), bias {}, {}", GREEN, exp, RESET, exp, params.exp_bias, unbiased),
+      format!("significand {}{}{} (0x{:0width$x})", YELLOW, sig, RESET, sig, width = sig_hex_width),
+    ];
+    let s = format!("{}{}{}", CYAN, sign, RESET);
+    let f = format!("{}{}{}/2^{}", YELLOW, sig, RESET, params.sig_bits);
+    let formula = if class.inf() {
+      "exponent all ones and significand zero: infinity".to_owned()
+    } else if class.nan() {
+      format!("exponent all ones and significand non-zero: {} NaN", if class == FloatClass::QuietNaN { "quiet" } else { "signalling" })
+    } else if class.normal() {
+      format!("(-1)^s × 1.f × 2^(e-bias) = (-1)^{} × (1 + {}) × 2^({}{}{}-{})", s, f, GREEN, exp, RESET, params.exp_bias)
+    } else {
+      // subnormals and zeros have no implicit bit, and use the smallest normal exponent
+      format!("(-1)^s × 0.f × 2^(1-bias) = (-1)^{} × {} × 2^(1-{})", s, f, params.exp_bias)
+    };
+    lines.push(formula);
+    lines
+  }
+}
@@ -1,6 +1,8 @@
-use crate::floats::{Float, FloatClass};
-use crate::printers::Printer;
-use crate::str_conv::float_to_exact_str;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use crate::floats::{pow2, Float, FloatClass};
+use crate::printers::{Printer, DARK_GRAY, GREEN, RESET};
+use crate::str_conv::{float_to_exact_str, rational_to_sci_str};
 
 pub struct ExactDecimalPrinter;
 
@@ -24,4 +26,98 @@ impl Printer for ExactDecimalPrinter {
     };
     vec![s]
   }
-}
\ No newline at end of file
+}
+
+/// Prints the value in scientific notation, rounded to a number of significant digits.
+/// Digits that are the same for every real number that rounds to this float are highlighted,
+/// the remaining digits are noise.
+pub struct ScientificPrinter {
+  /// The number of significant digits. If unset, enough digits to round-trip the format are used.
+  pub digits: Option<usize>,
+}
+
+/// Returns the interval of real numbers that round to `val` under round-to-nearest.
+fn rounding_interval(val: &Float) -> (BigRational, BigRational) {
+  let value = val.exact_value().unwrap();
+  let ulp = pow2(val.exponent_logical() - val.params().sig_bits as i64);
+  // at the bottom of a binade the next float down is only half an ULP away
+  let ulp_below = if val.classify().normal() && val.significand_bits().not_any() && val.exponent_logical() > val.params().min_exp() {
+    &ulp / BigInt::from(2)
+  } else {
+    ulp.clone()
+  };
+  let half = BigRational::new(BigInt::from(1), BigInt::from(2));
+  (&value - ulp_below * &half, &value + ulp * &half)
+}
+
+impl Printer for ScientificPrinter {
+  fn name(&self) -> &str {
+    "Scientific"
+  }
+
+  fn description(&self) -> &str {
+    "Prints the value in scientific notation with --digits significant digits, marking the digits that are noise"
+  }
+
+  fn print(&self, val: &Float) -> Vec<String> {
+    let class = val.classify();
+    if !class.finite() {
+      return ExactDecimalPrinter.print(val);
+    }
+    let sign = if val.sign() { "-" } else { "" };
+    if class.zero() {
+      return vec![format!("{}0", sign)];
+    }
+    let digits = self.digits.unwrap_or(val.params().pmin).max(1);
+    let s = rational_to_sci_str(&val.exact_value().unwrap(), digits);
+
+    // the leading digits are correct if every value in the rounding interval rounds to them
+    let (low, high) = rounding_interval(val);
+    let correct = (1..=digits).rev()
+      .find(|&i| rational_to_sci_str(&low, i) == rational_to_sci_str(&high, i))
+      .unwrap_or(0);
+
+    // the mantissa has a decimal point after the first digit
+    let split = if correct == 0 { 0 } else if correct == 1 { 1 } else { correct + 1 };
+    let (mantissa, exponent) = s.split_at(s.find('e').unwrap());
+    let (good, noise) = mantissa.split_at(split.min(mantissa.len()));
+    vec![
+      format!("{}{}{}{}{}{}{}", sign, GREEN, good, DARK_GRAY, noise, RESET, exponent),
+      format!("{} significant digits, {} correct", digits, correct),
+    ]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::floats::{FloatParameters, F16_PARAMS, F32_PARAMS, F64_PARAMS};
+
+  fn sci(digits: Option<usize>, s: &str, params: &FloatParameters) -> Vec<String> {
+    ScientificPrinter { digits }.print(&Float::parse(s, params).unwrap())
+  }
+
+  #[test]
+  fn counts_correct_digits() {
+    assert_eq!(sci(None, "0.1", &F32_PARAMS), ["1.00000001e-01", "9 significant digits, 7 correct"]);
+    assert_eq!(sci(None, "0.1", &F64_PARAMS), ["1.0000000000000001e-01", "17 significant digits, 16 correct"]);
+    assert_eq!(sci(None, "1000", &F16_PARAMS), ["1.0000e+03", "5 significant digits, 3 correct"]);
+    // the interval around the smallest subnormal spans several leading digits
+    assert_eq!(sci(None, "0x00000001", &F32_PARAMS)[1], "9 significant digits, 0 correct");
+  }
+
+  #[test]
+  fn digits_and_special_values() {
+    assert_eq!(sci(Some(3), "-0.1", &F32_PARAMS), ["-1.00e-01", "3 significant digits, 3 correct"]);
+    assert_eq!(sci(None, "-0", &F32_PARAMS), ["-0"]);
+    assert_eq!(sci(None, "-inf", &F32_PARAMS), ["-Inf"]);
+  }
+
+  #[test]
+  fn rounding_interval_is_narrower_below_a_power_of_two() {
+    let one = Float::parse("1", &F32_PARAMS).unwrap();
+    let (low, high) = rounding_interval(&one);
+    let value = one.exact_value().unwrap();
+    assert_eq!((&high - &value), (&value - &low) * BigInt::from(2));
+  }
+}
@@ -0,0 +1,41 @@
+use num_bigint::{BigInt, BigUint};
+use crate::floats::{bits_to_biguint, Float};
+use crate::printers::Printer;
+
+pub struct IntegerPrinter;
+
+impl Printer for IntegerPrinter {
+  fn name(&self) -> &str {
+    "Integer"
+  }
+
+  fn description(&self) -> &str {
+    "Prints the bits reinterpreted as integers, including the mapping that orders integers like floats"
+  }
+
+  fn print(&self, val: &Float) -> Vec<String> {
+    let len = val.params().total_length();
+    let unsigned = bits_to_biguint(val.bits());
+    let sign_bit = BigUint::from(1u32) << (len - 1);
+    let signed = if val.sign() { BigInt::from(unsigned.clone()) - (BigInt::from(1) << len) } else { BigInt::from(unsigned.clone()) };
+    // flipping all bits of negatives and the sign bit of positives gives an unsigned key that sorts like the floats
+    let all_ones = (BigUint::from(1u32) << len) - 1u32;
+    let radix_key = if val.sign() { &unsigned ^ &all_ones } else { &unsigned ^ &sign_bit };
+
+    let class = val.classify();
+    let order = if class.positive() {
+      "non-negative: signed, unsigned and ordered integers all order like the floats"
+    } else if class.negative() {
+      "negative: as a signed integer it orders in reverse, the ordered mapping negates the magnitude instead"
+    } else {
+      "NaN: orders beyond the infinity of the same sign, as in IEEE 754 totalOrder"
+    };
+    vec![
+      format!("unsigned    u{:<4} {}", len, unsigned),
+      format!("signed      i{:<4} {}", len, signed),
+      format!("ordered     i{:<4} {} (sign-magnitude to two's complement, used for ULP distance)", len, val.ordered_integer()),
+      format!("radix key   u{:<4} {} (0x{:0width$x}, flip all bits if negative, else the sign bit)", len, radix_key, radix_key, width = len.div_ceil(4)),
+      order.to_owned(),
+    ]
+  }
+}
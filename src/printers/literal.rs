@@ -0,0 +1,82 @@
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
+use crate::eval::{format_error, format_real};
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{Float, F64_PARAMS};
+use crate::printers::Printer;
+use crate::str_conv::{parse_decimal, rational_to_exact_str, rational_to_sci_str, DecimalLiteral};
+
+/// Returns the number of significant digits needed to write a terminating decimal.
+fn significant_digits(value: &BigRational) -> usize {
+  let s = rational_to_exact_str(value).unwrap_or_default();
+  s.trim_start_matches(['-', '0', '.']).replace('.', "").trim_end_matches('0').len()
+}
+
+/// Explains how a decimal literal was rounded when it was stored in `value`.
+/// Returns `None` if `literal` is not a finite decimal number.
+pub fn literal_report(literal: &str, value: &Float) -> Option<Vec<String>> {
+  let Some(DecimalLiteral::Finite(sign, magnitude)) = parse_decimal(literal) else {
+    return None;
+  };
+  let exact = if sign { -magnitude.clone() } else { magnitude.clone() };
+  let params = value.params();
+  let mut lines = vec![format!("{} = {}", literal, format_real(&exact))];
+
+  let Some(stored) = value.exact_signed_value() else {
+    lines.push(format!("not representable: the literal overflows {} and is stored as infinity", params));
+    return Some(lines);
+  };
+  let error = &stored - &exact;
+  if error.is_zero() {
+    lines.push(format!("exactly representable in {}", params));
+  } else {
+    let direction = if error.is_positive() { "up (toward +inf)" } else { "down (toward -inf)" };
+    let extra = if stored.is_zero() { ", underflowing to zero" } else { "" };
+    lines.push(format!("not exactly representable in {}, rounded {}{}", params, direction, extra));
+    lines.push(format!("stored:         {}", format_real(&stored)));
+    lines.push(format!("absolute error: {}", format_error(&error, value)));
+    if !exact.is_zero() {
+      lines.push(format!("relative error: {}", rational_to_sci_str(&(&error / exact.abs()), 6)));
+    }
+    // a denominator with a factor other than 2 never terminates in binary
+    if magnitude.denom().magnitude().count_ones() != 1 {
+      lines.push("its denominator is not a power of two, so no binary format can store it exactly".to_owned());
+    }
+  }
+
+  let digits = significant_digits(&magnitude);
+  if !error.is_zero() && digits > params.pmin {
+    lines.push(format!("the literal has {} significant digits, but {} values are identified by {}: the extra digits cannot all be kept",
+      digits, params, params.pmin));
+  }
+
+  // the same literal in double precision, which is what an unsuffixed literal means in most languages
+  if !error.is_zero() && params != &F64_PARAMS {
+    let (double, _) = FloatingPointEnv::default().round(&mut String::new(), sign, &magnitude, &F64_PARAMS).unwrap();
+    if let Some(double) = double.exact_signed_value().filter(|v| v != &stored) {
+      lines.push(format!("as f64 it is {}, so the two are not equal", format_real(&double)));
+    }
+  }
+  Some(lines)
+}
+
+/// Prints [`literal_report`] for the literal that the float was parsed from.
+pub struct LiteralPrinter {
+  /// The value given to `show`, if any.
+  pub literal: Option<String>,
+}
+
+impl Printer for LiteralPrinter {
+  fn name(&self) -> &str {
+    "Literal"
+  }
+
+  fn description(&self) -> &str {
+    "Explains how the decimal literal given to show was rounded"
+  }
+
+  fn print(&self, val: &Float) -> Vec<String> {
+    self.literal.as_deref().and_then(|v| literal_report(v, val))
+      .unwrap_or_else(|| vec!["only available for a decimal literal given to show".to_owned()])
+  }
+}
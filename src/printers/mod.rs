@@ -1,33 +1,113 @@
 pub mod binary;
 pub mod human;
 pub mod epsilon;
+pub mod source;
+pub mod fields;
+pub mod bytes;
+pub mod integer;
+pub mod number_line;
+pub mod literal;
 
-use std::collections::{BTreeMap, HashMap};
-use std::fmt::{Write};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::{Display, Formatter, Write};
+use std::io::IsTerminal;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use clap::ValueEnum;
 use crate::floats::{Float, BitSlice};
-use crate::printers::binary::{BinaryPrinter, BinaryPrinterWithGuide};
+use crate::ops::Value;
+use crate::printers::binary::BinaryPrinterWithGuide;
 use crate::printers::epsilon::UnitInLastPlacePrinter;
-use crate::printers::human::ExactDecimalPrinter;
-
-pub const RESET: &str = "\x1b[0m";
-pub const BLACK: &str = "\x1b[30m";
-pub const DARK_RED: &str = "\x1b[31m";
-pub const DARK_GREEN: &str = "\x1b[32m";
-pub const DARK_YELLOW: &str = "\x1b[33m";
-pub const DARK_BLUE: &str = "\x1b[34m";
-pub const DARK_PINK: &str = "\x1b[35m";
-pub const DARK_CYAN: &str = "\x1b[36m";
-pub const GRAY: &str = "\x1b[37m";
-
-pub const DARK_GRAY: &str = "\x1b[90m";
-pub const RED: &str = "\x1b[91m";
-pub const GREEN: &str = "\x1b[92m";
-pub const YELLOW: &str = "\x1b[93m";
-pub const BLUE: &str = "\x1b[94m";
-pub const PINK: &str = "\x1b[95m";
-pub const CYAN: &str = "\x1b[96m";
-pub const WHITE: &str = "\x1b[97m";
+use crate::printers::human::{ExactDecimalPrinter, ScientificPrinter};
+use crate::printers::source::SourceLiteralPrinter;
+use crate::printers::fields::FieldsPrinter;
+use crate::printers::bytes::ByteLayoutPrinter;
+use crate::printers::integer::IntegerPrinter;
+use crate::printers::number_line::NumberLinePrinter;
+use crate::printers::literal::LiteralPrinter;
+
+/// An ANSI escape sequence. It is only written out if colored output is enabled,
+/// see [`set_color_choice`]. Errors go to stderr, which decides separately, see [`print_error`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Color(&'static str);
+
+impl Display for Color {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    if colors_enabled() {
+      f.write_str(self.0)
+    } else {
+      Ok(())
+    }
+  }
+}
+
+pub const RESET: Color = Color("\x1b[0m");
+pub const BOLD: Color = Color("\x1b[1m");
+pub const DARK_GREEN: Color = Color("\x1b[32m");
+pub const DARK_YELLOW: Color = Color("\x1b[33m");
+pub const DARK_CYAN: Color = Color("\x1b[36m");
+
+pub const DARK_GRAY: Color = Color("\x1b[90m");
+pub const RED: Color = Color("\x1b[91m");
+pub const GREEN: Color = Color("\x1b[92m");
+pub const YELLOW: Color = Color("\x1b[93m");
+pub const PINK: Color = Color("\x1b[95m");
+pub const CYAN: Color = Color("\x1b[96m");
+
+/// When to emit colored output.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum ColorChoice {
+  /// Use colors on each output stream that is a terminal, unless `NO_COLOR` is set.
+  #[default]
+  Auto,
+  /// Always use colors.
+  Always,
+  /// Never use colors.
+  Never,
+}
+
+impl ColorChoice {
+  /// Decides whether colors should be used on `stream` in the current environment.
+  pub fn resolve(self, stream: &impl IsTerminal) -> bool {
+    match self {
+      ColorChoice::Always => true,
+      ColorChoice::Never => false,
+      ColorChoice::Auto => {
+        // https://no-color.org: any non-empty value disables colors
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        !no_color && stream.is_terminal()
+      }
+    }
+  }
+}
+
+static COLORS_ENABLED: AtomicBool = AtomicBool::new(false);
+static ERROR_COLORS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_color_choice(choice: ColorChoice) {
+  COLORS_ENABLED.store(choice.resolve(&std::io::stdout()), Ordering::Relaxed);
+  ERROR_COLORS_ENABLED.store(choice.resolve(&std::io::stderr()), Ordering::Relaxed);
+}
+
+pub fn colors_enabled() -> bool {
+  COLORS_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Prints an error to stderr, in red if colors are enabled for stderr.
+pub fn print_error(message: impl Display) {
+  if ERROR_COLORS_ENABLED.load(Ordering::Relaxed) {
+    eprintln!("{}{}{}", RED.0, message, RESET.0);
+  } else {
+    eprintln!("{}", message);
+  }
+}
+
+/// Separates the sign, exponent and significand fields.
+/// Without colors the fields would be hard to tell apart, so a visible character is used instead.
+pub fn field_separator() -> char {
+  if colors_enabled() { ' ' } else { '|' }
+}
 
 pub fn bit2char(bit: bool) -> char {
   if bit { '1' } else { '0' }
@@ -55,10 +135,90 @@ pub trait Printer {
   fn print(&self, val: &Float) -> Vec<String>;
 }
 
-pub fn collect_printers() -> BTreeMap<String, Rc<dyn Printer>> {
+/// Settings shared by the printers, set from the command line.
+#[derive(Clone, Debug, Default)]
+pub struct PrinterOptions {
+  /// The number of significant digits of decimal output.
+  pub digits: Option<usize>,
+  /// The decimal literal the printed float was parsed from, if any.
+  pub literal: Option<String>,
+}
+
+pub fn collect_printers(options: &PrinterOptions) -> BTreeMap<String, Rc<dyn Printer>> {
   let mut h: BTreeMap<String, Rc<dyn Printer>> = BTreeMap::new();
   h.insert("binary".into(), Rc::new(BinaryPrinterWithGuide));
   h.insert("exact".into(), Rc::new(ExactDecimalPrinter));
   h.insert("ulp".into(), Rc::new(UnitInLastPlacePrinter));
+  h.insert("line".into(), Rc::new(NumberLinePrinter { interval: true }));
+  h.insert("line-plain".into(), Rc::new(NumberLinePrinter { interval: false }));
+  h.insert("int".into(), Rc::new(IntegerPrinter));
+  h.insert("bytes".into(), Rc::new(ByteLayoutPrinter));
+  h.insert("fields".into(), Rc::new(FieldsPrinter));
+  h.insert("source".into(), Rc::new(SourceLiteralPrinter));
+  h.insert("sci".into(), Rc::new(ScientificPrinter { digits: options.digits }));
+  h.insert("literal".into(), Rc::new(LiteralPrinter { literal: options.literal.clone() }));
   h
-}
\ No newline at end of file
+}
+
+pub fn print_using_printer(printer: &dyn Printer, val: &Float) {
+  let strs = printer.print(val);
+  let pname = printer.name();
+  println!("{}: {}", pname, &strs[0]);
+  strs.iter().skip(1).for_each(|v| {
+    println!("{}  {}", " ".repeat(pname.len()), v);
+  });
+}
+
+/// Looks up the printers named in `show`, reporting the ones that don't exist.
+pub fn select_printers(show: &[String], printers: &BTreeMap<String, Rc<dyn Printer>>) -> Vec<(String, Rc<dyn Printer>)> {
+  show.iter().filter_map(|v| {
+    let p = printers.get(v).cloned();
+    if p.is_none() {
+      print_error(format_args!("Unknown printer: {}", v));
+    }
+    p.map(|p| (v.clone(), p))
+  }).collect()
+}
+
+pub fn print_float(value: &Float, show: &[String], printers: &BTreeMap<String, Rc<dyn Printer>>) {
+  select_printers(show, printers).iter().for_each(|(_, v)| print_using_printer(v.as_ref(), value));
+}
+
+/// Prints an op result, using the printers for floats.
+pub fn print_value(value: &Value, show: &[String], printers: &BTreeMap<String, Rc<dyn Printer>>) {
+  match value {
+    Value::Float(v) => print_float(v, show, printers),
+    Value::Bool(v) => println!("{}", v),
+    Value::Int(v) => {
+      println!("{} ({})", v.value, v.ty);
+      println!("0x{:0width$x}", v.ty.encode(&v.value), width = v.ty.bits.div_ceil(4));
+      let bits = v.bit_string();
+      if v.ty.signed {
+        println!("{}{}{}{}", CYAN, &bits[..1], RESET, &bits[1..]);
+      } else {
+        println!("{}", bits);
+      }
+    }
+    Value::Tuple(values) => values.iter().for_each(|(name, v)| {
+      println!("{}{}{}", BOLD, name, RESET);
+      print_value(v, show, printers);
+    }),
+    Value::Undefined(v) => println!("{}{}{}", RED, v, RESET),
+  }
+}
+
+/// Returns the number of characters in `s` that are visible, i.e. excluding ANSI escape sequences.
+pub fn visible_len(s: &str) -> usize {
+  let mut len = 0;
+  let mut in_escape = false;
+  for c in s.chars() {
+    if in_escape {
+      in_escape = !c.is_ascii_alphabetic();
+    } else if c == '\x1b' {
+      in_escape = true;
+    } else {
+      len += 1;
+    }
+  }
+  len
+}
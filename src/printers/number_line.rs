@@ -0,0 +1,95 @@
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+use crate::floats::Float;
+use crate::printers::{Printer, BOLD, DARK_GRAY, RESET, YELLOW};
+use crate::str_conv::rational_to_sci_str;
+
+/// How many floats are shown on each side of the value.
+const NEIGHBOURS: usize = 4;
+const WIDTH: usize = 64;
+
+pub struct NumberLinePrinter {
+  /// Whether to draw the rounding interval of the value below the line.
+  pub interval: bool,
+}
+
+/// Collects up to `count` finite floats in one direction, nearest first.
+fn neighbours(val: &Float, count: usize, up: bool) -> Vec<Float> {
+  let mut out = Vec::new();
+  let mut current = val.clone();
+  while out.len() < count {
+    let next = if up { current.next_up() } else { current.next_down() };
+    if !next.classify().finite() || next.bits() == current.bits() {
+      break;
+    }
+    out.push(next.clone());
+    current = next;
+  }
+  out
+}
+
+impl Printer for NumberLinePrinter {
+  fn name(&self) -> &str {
+    "Number Line"
+  }
+
+  fn description(&self) -> &str {
+    if self.interval {
+      "Plots the neighbouring floats on a linear scale, with the value and its rounding interval marked"
+    } else {
+      "Plots the neighbouring floats on a linear scale, with the value marked"
+    }
+  }
+
+  fn print(&self, val: &Float) -> Vec<String> {
+    if !val.classify().finite() {
+      return vec!["Undefined".into()];
+    }
+    let below = neighbours(val, NEIGHBOURS, false);
+    let above = neighbours(val, NEIGHBOURS, true);
+    let x = val.exact_signed_value().unwrap();
+    let values = below.iter().rev().chain(above.iter())
+      .map(|v| v.exact_signed_value().unwrap())
+      .collect::<Vec<_>>();
+    let lo = values.first().cloned().unwrap_or(x.clone()).min(x.clone());
+    let hi = values.last().cloned().unwrap_or(x.clone()).max(x.clone());
+    let span = &hi - &lo;
+    let column = |v: &BigRational| -> usize {
+      if span.is_zero() {
+        return 0;
+      }
+      ((v - &lo) / &span * BigRational::from_integer((WIDTH as i64 - 1).into())).round().to_usize().unwrap_or(0)
+    };
+
+    let mut axis = vec!['─'; WIDTH];
+    values.iter().for_each(|v| axis[column(v)] = '┼');
+    let x_col = column(&x);
+    let axis = axis.iter().enumerate().map(|(i, c)| {
+      if i == x_col { format!("{}{}┃{}", BOLD, YELLOW, RESET) } else { c.to_string() }
+    }).collect::<String>();
+
+    let marker = format!("{}^ x", " ".repeat(x_col));
+    let left = rational_to_sci_str(&lo, val.params().pmin);
+    let right = rational_to_sci_str(&hi, val.params().pmin);
+    let labels = format!("{}{}{}", left, " ".repeat(WIDTH.saturating_sub(left.len() + right.len())), right);
+    if !self.interval {
+      return vec![axis, marker, labels];
+    }
+
+    // the rounding interval reaches halfway to each neighbour
+    let two = BigRational::from_integer(2.into());
+    // at the largest finite values the interval is symmetric, up to the overflow threshold
+    let half_gap = |v: Option<&Float>| v.map(|v| (v.exact_signed_value().unwrap() - &x) / &two);
+    let low_gap = half_gap(below.first());
+    let high_gap = half_gap(above.first());
+    let low = &x + low_gap.clone().or(high_gap.clone().map(|v| -v)).unwrap_or_default();
+    let high = &x + high_gap.or(low_gap.map(|v| -v)).unwrap_or_default();
+    // without a neighbour on one side the interval reaches past the plotted floats, so it is cut off at the edge
+    let low_col = if low < lo { 0 } else { column(&low) }.min(WIDTH - 2);
+    let high_col = if high > hi { WIDTH - 1 } else { column(&high) }.max(low_col + 1);
+    let mut interval = " ".repeat(low_col);
+    interval += &format!("{}[{}]", DARK_GRAY, "─".repeat(high_col - low_col - 1));
+    interval += &format!(" rounds to x{}", RESET);
+    vec![axis, marker, interval, labels]
+  }
+}
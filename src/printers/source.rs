@@ -0,0 +1,275 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
+use crate::floats::{bits_to_biguint, Float, FloatClass, FloatParameters, BF16_PARAMS, F16_PARAMS, F32_PARAMS, F64_PARAMS};
+use crate::printers::{Printer, DARK_GRAY, RESET};
+
+/// The formats that have a type in at least one language.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Native {
+  F16,
+  BF16,
+  F32,
+  F64,
+}
+
+impl Native {
+  fn from_params(params: &FloatParameters) -> Option<Self> {
+    [(F16_PARAMS, Native::F16), (BF16_PARAMS, Native::BF16), (F32_PARAMS, Native::F32), (F64_PARAMS, Native::F64)]
+      .into_iter()
+      .find(|(p, _)| p == params)
+      .map(|(_, v)| v)
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Native::F16 => "float16",
+      Native::BF16 => "bfloat16",
+      Native::F32 => "float32",
+      Native::F64 => "float64",
+    }
+  }
+}
+
+/// Formats the magnitude of a finite float as a normalized hexadecimal float, e.g. `0x1.99999ap-4`.
+fn hex_float(val: &Float) -> String {
+  let sig = bits_to_biguint(&val.significand_logical());
+  if sig.is_zero() {
+    return "0x0p+0".to_owned();
+  }
+  let msb = sig.bits() as usize - 1;
+  let exp = val.exponent_logical() - val.params().sig_bits as i64 + msb as i64;
+  let frac = sig - (BigUint::from(1u32) << msb);
+  let mut digits = String::new();
+  if msb != 0 {
+    // pad the fraction to whole hex digits, then drop the trailing zeros
+    let hex_digits = msb.div_ceil(4);
+    let frac = frac << (hex_digits * 4 - msb);
+    digits = format!("{:0width$x}", frac, width = hex_digits).trim_end_matches('0').to_owned();
+  }
+  let point = if digits.is_empty() { "" } else { "." };
+  format!("0x1{}{}p{}{}", point, digits, if exp < 0 { "-" } else { "+" }, exp.unsigned_abs())
+}
+
+/// Formats the bits of a float as a hexadecimal integer.
+fn bits_hex(val: &Float) -> String {
+  format!("0x{:0width$x}", bits_to_biguint(val.bits()), width = val.params().total_length().div_ceil(4))
+}
+
+/// Returns whether `val` is the NaN that languages produce by default: positive, quiet and with an empty payload.
+fn is_default_nan(val: &Float) -> bool {
+  let sig = val.significand_bits();
+  val.classify() == FloatClass::QuietNaN && !val.sign() && sig[..sig.len() - 1].not_any()
+}
+
+/// Returns a C or C++ expression for `val`, using the given literal suffix and constants.
+/// `bit_cast` turns the bits of a NaN into an expression.
+fn c_family(val: &Float, suffix: &str, inf: &str, nan: &str, bit_cast: &dyn Fn(&str) -> String) -> String {
+  let sign = if val.sign() { "-" } else { "" };
+  let class = val.classify();
+  if class.zero() {
+    format!("{}0.0{}", sign, suffix)
+  } else if class.finite() {
+    format!("{}{}{}", sign, hex_float(val), suffix)
+  } else if class.inf() {
+    format!("{}{}", sign, inf)
+  } else if is_default_nan(val) {
+    nan.to_owned()
+  } else {
+    bit_cast(&bits_hex(val))
+  }
+}
+
+fn c_literal(val: &Float, native: Native) -> Option<String> {
+  let (ctype, suffix, int_type, inf, nan) = match native {
+    Native::F16 => ("_Float16", "f16", "uint16_t", "(_Float16)INFINITY", "(_Float16)NAN"),
+    Native::BF16 => return None,
+    Native::F32 => ("float", "f", "uint32_t", "INFINITY", "NAN"),
+    Native::F64 => ("double", "", "uint64_t", "HUGE_VAL", "(double)NAN"),
+  };
+  Some(c_family(val, suffix, inf, nan, &|bits| format!("((union {{ {} i; {} f; }}){{ {} }}).f", int_type, ctype, bits)))
+}
+
+fn cpp_literal(val: &Float, native: Native) -> Option<String> {
+  let (ctype, suffix, int_type) = match native {
+    Native::F16 => ("std::float16_t", "f16", "std::uint16_t"),
+    Native::BF16 => ("std::bfloat16_t", "bf16", "std::uint16_t"),
+    Native::F32 => ("float", "f", "std::uint32_t"),
+    Native::F64 => ("double", "", "std::uint64_t"),
+  };
+  let inf = format!("std::numeric_limits<{}>::infinity()", ctype);
+  let nan = format!("std::numeric_limits<{}>::quiet_NaN()", ctype);
+  Some(c_family(val, suffix, &inf, &nan, &|bits| format!("std::bit_cast<{}>({}{{{}}})", ctype, int_type, bits)))
+}
+
+fn rust_literal(val: &Float, native: Native) -> Option<String> {
+  let ty = match native {
+    Native::F16 => "f16",
+    Native::BF16 => return None,
+    Native::F32 => "f32",
+    Native::F64 => "f64",
+  };
+  let class = val.classify();
+  Some(if class.zero() {
+    format!("{}0.0{}", if val.sign() { "-" } else { "" }, ty)
+  } else if class == FloatClass::PositiveInf {
+    format!("{}::INFINITY", ty)
+  } else if class == FloatClass::NegativeInf {
+    format!("{}::NEG_INFINITY", ty)
+  } else if is_default_nan(val) {
+    format!("{}::NAN", ty)
+  } else {
+    // Rust has no hexadecimal float literals
+    format!("{}::from_bits({})", ty, bits_hex(val))
+  })
+}
+
+fn python_literal(val: &Float, native: Native) -> Option<String> {
+  let sign = if val.sign() { "-" } else { "" };
+  let class = val.classify();
+  match native {
+    // Python floats are doubles, other formats need numpy
+    Native::F16 => Some(format!("numpy.uint16({}).view(numpy.float16)", bits_hex(val))),
+    Native::BF16 => None,
+    Native::F32 => Some(format!("numpy.uint32({}).view(numpy.float32)", bits_hex(val))),
+    Native::F64 => Some(if class.zero() {
+      format!("{}0.0", sign)
+    } else if class.finite() {
+      format!("float.fromhex('{}{}')", sign, hex_float(val))
+    } else if class.inf() {
+      format!("float('{}inf')", sign)
+    } else if is_default_nan(val) {
+      "float('nan')".to_owned()
+    } else {
+      format!("struct.unpack('>d', bytes.fromhex('{}'))[0]", &bits_hex(val)[2..])
+    }),
+  }
+}
+
+fn java_literal(val: &Float, native: Native) -> Option<String> {
+  let (class_name, suffix, from_bits, int_suffix) = match native {
+    // widened to float, there is no half precision type
+    Native::F16 => return Some(format!("Float.float16ToFloat((short) {})", bits_hex(val))),
+    Native::BF16 => return None,
+    Native::F32 => ("Float", "f", "intBitsToFloat", ""),
+    Native::F64 => ("Double", "", "longBitsToDouble", "L"),
+  };
+  let sign = if val.sign() { "-" } else { "" };
+  let class = val.classify();
+  Some(if class.zero() {
+    format!("{}0.0{}", sign, suffix)
+  } else if class.finite() {
+    format!("{}{}{}", sign, hex_float(val), suffix)
+  } else if class == FloatClass::PositiveInf {
+    format!("{}.POSITIVE_INFINITY", class_name)
+  } else if class == FloatClass::NegativeInf {
+    format!("{}.NEGATIVE_INFINITY", class_name)
+  } else if is_default_nan(val) {
+    format!("{}.NaN", class_name)
+  } else {
+    format!("{}.{}({}{})", class_name, from_bits, bits_hex(val), int_suffix)
+  })
+}
+
+fn go_literal(val: &Float, native: Native) -> Option<String> {
+  let class = val.classify();
+  match native {
+    Native::F16 | Native::BF16 => None,
+    Native::F32 => Some(format!("math.Float32frombits({})", bits_hex(val))),
+    // constant expressions can't be negative zero, and math.NaN() has a payload
+    Native::F64 => Some(match class {
+      FloatClass::PositiveZero => "0.0".to_owned(),
+      FloatClass::NegativeZero => "math.Copysign(0, -1)".to_owned(),
+      FloatClass::PositiveInf => "math.Inf(1)".to_owned(),
+      FloatClass::NegativeInf => "math.Inf(-1)".to_owned(),
+      FloatClass::QuietNaN | FloatClass::SignallingNaN => format!("math.Float64frombits({})", bits_hex(val)),
+      _ => format!("{}{}", if val.sign() { "-" } else { "" }, hex_float(val)),
+    }),
+  }
+}
+
+/// Returns the expression for a value in one language, or `None` if the language has no type for the format.
+type LiteralFn = fn(&Float, Native) -> Option<String>;
+
+pub struct SourceLiteralPrinter;
+
+impl Printer for SourceLiteralPrinter {
+  fn name(&self) -> &str {
+    "Source Literal"
+  }
+
+  fn description(&self) -> &str {
+    "Prints an expression that reproduces the exact bits in C, C++, Rust, Python, Java and Go"
+  }
+
+  fn print(&self, val: &Float) -> Vec<String> {
+    let Some(native) = Native::from_params(val.params()) else {
+      return vec![format!("No language has a {} type, the bits are {}", val.params(), bits_hex(val))];
+    };
+    let languages: [(&str, LiteralFn); 6] = [
+      ("C", c_literal),
+      ("C++", cpp_literal),
+      ("Rust", rust_literal),
+      ("Python", python_literal),
+      ("Java", java_literal),
+      ("Go", go_literal),
+    ];
+    languages.iter().map(|(lang, literal)| {
+      let s = literal(val, native)
+        .unwrap_or_else(|| format!("{}no {} type{}", DARK_GRAY, native.name(), RESET));
+      format!("{:<8}{}", format!("{}:", lang), s)
+    }).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn float(s: &str, params: &FloatParameters) -> Float {
+    Float::parse(s, params).unwrap()
+  }
+
+  #[test]
+  fn hex_floats() {
+    assert_eq!(hex_float(&float("0.1", &F32_PARAMS)), "0x1.99999ap-4");
+    assert_eq!(hex_float(&float("1", &F64_PARAMS)), "0x1p+0");
+    assert_eq!(hex_float(&float("-1.5", &F16_PARAMS)), "0x1.8p+0");
+    // subnormals are normalized
+    assert_eq!(hex_float(&float("0x00000001", &F32_PARAMS)), "0x1p-149");
+    assert_eq!(hex_float(&float("0x00000003", &F32_PARAMS)), "0x1.8p-148");
+  }
+
+  #[test]
+  fn literals_per_language() {
+    let lines = SourceLiteralPrinter.print(&float("-0.1", &F64_PARAMS));
+    assert_eq!(lines, [
+      "C:      -0x1.999999999999ap-4",
+      "C++:    -0x1.999999999999ap-4",
+      "Rust:   f64::from_bits(0xbfb999999999999a)",
+      "Python: float.fromhex('-0x1.999999999999ap-4')",
+      "Java:   -0x1.999999999999ap-4",
+      "Go:     -0x1.999999999999ap-4",
+    ]);
+  }
+
+  #[test]
+  fn special_values() {
+    let neg_zero = float("-0", &F64_PARAMS);
+    assert_eq!(go_literal(&neg_zero, Native::F64).unwrap(), "math.Copysign(0, -1)");
+    assert_eq!(rust_literal(&neg_zero, Native::F64).unwrap(), "-0.0f64");
+    let nan = float("0x7fc00000", &F32_PARAMS);
+    assert_eq!(c_literal(&nan, Native::F32).unwrap(), "NAN");
+    assert_eq!(java_literal(&nan, Native::F32).unwrap(), "Float.NaN");
+    // a NaN with a payload needs its bits
+    let snan = float("0x7f800001", &F32_PARAMS);
+    assert_eq!(java_literal(&snan, Native::F32).unwrap(), "Float.intBitsToFloat(0x7f800001)");
+    assert_eq!(cpp_literal(&snan, Native::F32).unwrap(), "std::bit_cast<float>(std::uint32_t{0x7f800001})");
+    assert_eq!(python_literal(&float("-inf", &F64_PARAMS), Native::F64).unwrap(), "float('-inf')");
+  }
+
+  #[test]
+  fn formats_without_a_type() {
+    assert_eq!(rust_literal(&float("1", &BF16_PARAMS), Native::BF16), None);
+    assert_eq!(SourceLiteralPrinter.print(&float("1", &FloatParameters::new(3, 2))), ["No language has a custom(3, 2) type, the bits are 0x0c"]);
+  }
+}
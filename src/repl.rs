@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::rc::Rc;
+use thiserror::Error;
+use crate::eval::{EvalError, Evaluator, Step};
+use crate::expr::{parse_statement, Expr, ExprError, Statement};
+use crate::fenv::FloatingPointEnv;
+use crate::floats::{Float, FloatParameters, FloatParseError, F64_PARAMS};
+use crate::ops::{Exception, Op, Value};
+use crate::printers::{print_error, print_float, print_value, Printer, BOLD, RESET};
+
+const HELP: &str = "\
+Statements:
+  a = f32 0.1          Bind a variable to a typed literal
+  a = a + b            Bind a variable to the result of an expression
+  a * 3                Evaluate an expression, the result is bound to _
+  convert(a, bf16)     Call an operation, a trailing type selects the output format
+  to_int(a, u8, java)  Settings of an operation follow its operands
+  !N                   Re-run entry N from the history
+Commands:
+  :round [MODE]        Show or set the rounding mode (e.g. TiesToEven, TowardZero)
+  :ftz [on|off]        Show or set flushing subnormal results to zero
+  :type [TYPE|auto]    Show or set the format of results and untyped literals
+  :show [PRINTER...]   Show or set the printers used to display results
+  :trace [on|off]      Show or set printing the steps of each operation
+  :vars                List all variables
+  :ops                 List all operations
+  :history             List previous inputs
+  :help                Show this message
+  :quit                Exit";
+
+#[derive(Error, Debug)]
+pub enum ReplError {
+  #[error("{0}")]
+  Expr(#[from] ExprError),
+  #[error("{0}")]
+  Eval(#[from] EvalError),
+  #[error("Error parsing type \"{0}\": {1}")]
+  Type(String, FloatParseError),
+  #[error("Unknown command: {0}")]
+  UnknownCommand(String),
+  #[error("No history entry {0}")]
+  NoHistory(String),
+  #[error("{0}")]
+  InvalidArgument(String),
+}
+
+/// An interactive session, which keeps variables and settings between inputs.
+pub struct Repl {
+  printers: BTreeMap<String, Rc<dyn Printer>>,
+  ops: BTreeMap<String, Rc<dyn Op>>,
+  vars: BTreeMap<String, Float>,
+  env: FloatingPointEnv,
+  /// The format of untyped literals and of results. If unset, literals are doubles and
+  /// results have the type of the first operand.
+  output_type: Option<FloatParameters>,
+  show: Vec<String>,
+  trace: bool,
+  history: Vec<String>,
+}
+
+impl Repl {
+  pub fn new(printers: BTreeMap<String, Rc<dyn Printer>>, ops: BTreeMap<String, Rc<dyn Op>>, show: Vec<String>) -> Self {
+    Repl {
+      printers,
+      ops,
+      vars: BTreeMap::new(),
+      env: FloatingPointEnv::default(),
+      output_type: None,
+      show,
+      trace: false,
+      history: Vec::new(),
+    }
+  }
+
+  pub fn run(&mut self, input: impl BufRead) {
+    let interactive = std::io::stdin().is_terminal();
+    if interactive {
+      println!("Type :help for help, :quit to exit.");
+    }
+    let mut lines = input.lines();
+    loop {
+      if interactive {
+        print!("> ");
+        std::io::stdout().flush().unwrap();
+      }
+      let Some(Ok(line)) = lines.next() else {
+        break;
+      };
+      match self.execute_line(&line) {
+        Ok(true) => {}
+        Ok(false) => break,
+        Err(e) => print_error(e),
+      }
+    }
+  }
+
+  /// Runs one line of input, returns whether the session should continue.
+  fn execute_line(&mut self, line: &str) -> Result<bool, ReplError> {
+    let line = line.trim();
+    if line.is_empty() {
+      return Ok(true);
+    }
+    let line = match line.strip_prefix('!') {
+      Some(n) => {
+        let entry = n.parse::<usize>().ok()
+          .and_then(|i| self.history.get(i.wrapping_sub(1)))
+          .ok_or_else(|| ReplError::NoHistory(n.to_owned()))?;
+        println!("{}", entry);
+        entry.clone()
+      }
+      None => line.to_owned(),
+    };
+    self.history.push(line.clone());
+
+    if let Some(cmd) = line.strip_prefix(':') {
+      let mut words = cmd.split_whitespace();
+      let name = words.next().unwrap_or_default();
+      return self.command(name, &words.collect::<Vec<_>>());
+    }
+
+    let (name, expr) = match parse_statement(&line)? {
+      Statement::Assign(name, expr) => (name, expr),
+      Statement::Expr(expr) => ("_".to_owned(), expr),
+    };
+    let (value, exception) = self.eval(&expr)?;
+    match value {
+      Value::Float(value) => {
+        println!("{}{}{} = {}", BOLD, name, RESET, value.params());
+        print_float(&value, &self.show, &self.printers);
+        print_exception(exception);
+        self.vars.insert(name, value);
+      }
+      // only floats can be used as operands, so other results aren't stored
+      value => {
+        println!("{}{}{}", BOLD, name, RESET);
+        print_value(&value, &self.show, &self.printers);
+        print_exception(exception);
+      }
+    }
+    Ok(true)
+  }
+
+  fn command(&mut self, name: &str, args: &[&str]) -> Result<bool, ReplError> {
+    match name {
+      "q" | "quit" | "exit" => return Ok(false),
+      "help" => println!("{}", HELP),
+      "round" => {
+        if let Some(mode) = args.first() {
+          self.env.rounding_mode = mode.parse().map_err(ReplError::InvalidArgument)?;
+        }
+        println!("Rounding mode: {:?}", self.env.rounding_mode);
+      }
+      "ftz" => {
+        if let Some(v) = args.first() {
+          self.env.flush_subnormals_to_zero = parse_switch(v)?;
+        }
+        println!("Flush subnormals to zero: {}", self.env.flush_subnormals_to_zero);
+      }
+      "type" => {
+        if !args.is_empty() {
+          let ty = args.join(" ");
+          self.output_type = if ty == "auto" {
+            None
+          } else {
+            Some(FloatParameters::parse(&ty).map_err(|e| ReplError::Type(ty, e))?)
+          };
+        }
+        match &self.output_type {
+          Some(ty) => println!("Type: {}", ty),
+          None => println!("Type: auto"),
+        }
+      }
+      "show" => {
+        if !args.is_empty() {
+          if let Some(unknown) = args.iter().find(|v| !self.printers.contains_key(**v)) {
+            return Err(ReplError::InvalidArgument(format!("Unknown printer: {}", unknown)));
+          }
+          self.show = args.iter().map(|v| v.to_string()).collect();
+        }
+        println!("Printers: {}", self.show.join(" "));
+      }
+      "trace" => {
+        if let Some(v) = args.first() {
+          self.trace = parse_switch(v)?;
+        }
+        println!("Trace: {}", self.trace);
+      }
+      "vars" => {
+        self.vars.iter().for_each(|(k, v)| {
+          println!("{}{}{} = {}", BOLD, k, RESET, v.params());
+          print_float(v, &self.show, &self.printers);
+        });
+      }
+      "ops" => self.ops.iter().for_each(|(k, v)| {
+        let settings = v.settings().iter().map(|v| format!(" [{}]", v)).collect::<String>();
+        println!("{}{}", k, settings);
+      }),
+      "history" => {
+        self.history.iter().enumerate().for_each(|(i, v)| println!("{:>4}  {}", i + 1, v));
+      }
+      _ => return Err(ReplError::UnknownCommand(name.to_owned())),
+    }
+    Ok(true)
+  }
+
+  fn eval(&self, expr: &Expr) -> Result<(Value, Exception), ReplError> {
+    let evaluator = Evaluator {
+      ops: &self.ops,
+      env: &self.env,
+      vars: &self.vars,
+      literal_type: self.output_type.as_ref().unwrap_or(&F64_PARAMS),
+      output_type: self.output_type.as_ref(),
+      trace: self.trace,
+    };
+    let step = evaluator.eval(expr)?;
+    print_traces(&step);
+    Ok((step.value.clone(), step.all_exceptions()))
+  }
+}
+
+/// Prints the steps of each operation, in the order they were executed.
+fn print_traces(step: &Step) {
+  step.children.iter().for_each(print_traces);
+  if let Some((name, trace)) = &step.trace {
+    println!("{}{}{}{}", BOLD, name, RESET, trace);
+    println!();
+  }
+}
+
+fn print_exception(exception: Exception) {
+  if exception != Exception::default() {
+    println!("Exceptions: {}", exception);
+  }
+}
+
+fn parse_switch(s: &str) -> Result<bool, ReplError> {
+  match s {
+    "on" | "true" | "1" => Ok(true),
+    "off" | "false" | "0" => Ok(false),
+    _ => Err(ReplError::InvalidArgument(format!("Expected on or off, got \"{}\"", s))),
+  }
+}
@@ -1,4 +1,8 @@
-use num_bigint::{BigInt, Sign};
+use std::cmp::Ordering;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
 use crate::floats::Float;
 
 // Assumes finite
@@ -8,7 +12,7 @@ pub fn float_to_exact_str(f: &Float) -> String {
   let exponent = f.exponent_logical();
   if exponent >= params.sig_bits as i64 {
     // precision greater than 1, shift significant left and convert to dec
-    significand <<= exponent;
+    significand <<= exponent - params.sig_bits as i64;
     let mut s = significand.to_string();
     if f.sign() {
       s.insert(0, '-');
@@ -46,4 +50,160 @@ pub fn float_to_exact_str(f: &Float) -> String {
     }
     s
   }
-}
\ No newline at end of file
+}
+/// A decimal literal, with finite values kept exact.
+pub enum DecimalLiteral {
+  Finite(bool, BigRational),
+  Inf(bool),
+  NaN,
+}
+
+/// Powers of ten beyond this get impractically large to compute with exactly.
+const MAX_DECIMAL_EXPONENT: u64 = 1_000_000;
+
+/// Parses a decimal literal such as `-1.25e-3`, `inf` or `nan` without rounding it.
+pub fn parse_decimal(s: &str) -> Option<DecimalLiteral> {
+  let (sign, s) = match s.as_bytes().first() {
+    Some(b'-') => (true, &s[1..]),
+    Some(b'+') => (false, &s[1..]),
+    _ => (false, s),
+  };
+  match s.to_ascii_lowercase().as_str() {
+    "inf" | "infinity" => return Some(DecimalLiteral::Inf(sign)),
+    "nan" => return Some(DecimalLiteral::NaN),
+    _ => {}
+  }
+  let (mantissa, exp) = match s.find(['e', 'E']) {
+    Some(i) => (&s[..i], s[i + 1..].parse::<i64>().ok()?),
+    None => (s, 0),
+  };
+  let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+  if int_part.is_empty() && frac_part.is_empty() {
+    return None;
+  }
+  if !int_part.bytes().chain(frac_part.bytes()).all(|v| v.is_ascii_digit()) {
+    return None;
+  }
+  let digits = BigInt::parse_bytes(format!("0{}{}", int_part, frac_part).as_bytes(), 10)?;
+  let exp = exp.checked_sub(frac_part.len() as i64)?;
+  if exp.unsigned_abs() > MAX_DECIMAL_EXPONENT {
+    return None;
+  }
+  let scale = BigInt::from(10).pow(exp.unsigned_abs() as u32);
+  let value = if exp >= 0 {
+    BigRational::from_integer(digits * scale)
+  } else {
+    BigRational::new(digits, scale)
+  };
+  Some(DecimalLiteral::Finite(sign, value))
+}
+
+/// Formats an integer in binary, with a binary point before the last `frac_bits` digits.
+pub fn format_binary_point(value: &BigUint, frac_bits: usize) -> String {
+  let s = format!("{:0width$b}", value, width = frac_bits + 1);
+  if frac_bits == 0 {
+    return s;
+  }
+  format!("{}.{}", &s[..s.len() - frac_bits], &s[s.len() - frac_bits..])
+}
+
+/// Formats a fraction as an exact decimal, in the same style as [`float_to_exact_str`].
+/// Returns `None` if the decimal expansion does not terminate.
+pub fn rational_to_exact_str(value: &BigRational) -> Option<String> {
+  let mut rest = value.denom().magnitude().clone();
+  let twos = rest.trailing_zeros().unwrap_or(0);
+  rest >>= twos;
+  let mut fives = 0;
+  while (&rest % 5u32).is_zero() {
+    rest /= 5u32;
+    fives += 1;
+  }
+  if !rest.is_one() {
+    return None;
+  }
+  let decimal_shift = twos.max(fives) as usize;
+  let digits = value.numer().magnitude() * BigUint::from(10u32).pow(decimal_shift as u32) / value.denom().magnitude();
+  let mut s = digits.to_string();
+  if decimal_shift != 0 && decimal_shift <= s.len() {
+    s.insert(s.len() - decimal_shift, '.');
+  } else if decimal_shift > s.len() {
+    s = format!(".{}{}", "0".repeat(decimal_shift - s.len()), s);
+  }
+  if value.is_negative() {
+    s.insert(0, '-');
+  }
+  Some(s)
+}
+
+/// Formats a fraction in scientific notation, correctly rounded to `digits` significant digits.
+pub fn rational_to_sci_str(value: &BigRational, digits: usize) -> String {
+  if value.is_zero() {
+    return "0".to_owned();
+  }
+  let digits = digits.max(1);
+  let num = value.numer().magnitude();
+  let den = value.denom().magnitude();
+  // estimate floor(log10(value)) from the bit lengths, then correct it
+  let mut exp10 = ((num.bits() as f64 - den.bits() as f64) * 2f64.log10()).floor() as i64;
+  let ten = BigUint::from(10u32);
+  let pow10 = |e: i64| ten.pow(e.unsigned_abs() as u32);
+  let scaled = |e: i64| if e >= 0 { (num.clone(), den * pow10(e)) } else { (num * pow10(e), den.clone()) };
+  loop {
+    let (n, d) = scaled(exp10);
+    if n < d {
+      exp10 -= 1;
+    } else if n >= &d * &ten {
+      exp10 += 1;
+    } else {
+      break;
+    }
+  }
+  let (n, d) = scaled(exp10 - digits as i64 + 1);
+  let (mut sig, rem) = n.div_rem(&d);
+  let round_up = match (rem << 1u8).cmp(&d) {
+    Ordering::Greater => true,
+    Ordering::Equal => sig.is_odd(),
+    Ordering::Less => false,
+  };
+  if round_up {
+    sig += 1u32;
+    if sig == ten.pow(digits as u32) {
+      sig /= 10u32;
+      exp10 += 1;
+    }
+  }
+  let s = sig.to_string();
+  let mantissa = if digits > 1 { format!("{}.{}", &s[..1], &s[1..]) } else { s };
+  format!("{}{}e{}{:02}", if value.is_negative() { "-" } else { "" }, mantissa, if exp10 < 0 { '-' } else { '+' }, exp10.unsigned_abs())
+}
+
+#[cfg(test)]
+mod tests {
+  use num_bigint::BigInt;
+  use super::*;
+
+  fn finite(s: &str) -> (bool, BigRational) {
+    match parse_decimal(s) {
+      Some(DecimalLiteral::Finite(sign, value)) => (sign, value),
+      _ => panic!("{} didn't parse as a finite literal", s),
+    }
+  }
+
+  #[test]
+  fn parse_decimal_is_exact() {
+    assert_eq!(finite("0.1"), (false, BigRational::new(BigInt::from(1), BigInt::from(10))));
+    assert_eq!(finite("-1.25e-3"), (true, BigRational::new(BigInt::from(1), BigInt::from(800))));
+    assert_eq!(finite("12E2"), (false, BigRational::from_integer(BigInt::from(1200))));
+    assert_eq!(finite(".5"), (false, BigRational::new(BigInt::from(1), BigInt::from(2))));
+  }
+
+  #[test]
+  fn parse_decimal_special_values_and_errors() {
+    assert!(matches!(parse_decimal("-Infinity"), Some(DecimalLiteral::Inf(true))));
+    assert!(matches!(parse_decimal("nan"), Some(DecimalLiteral::NaN)));
+    assert!(parse_decimal(".").is_none());
+    assert!(parse_decimal("1e").is_none());
+    assert!(parse_decimal("0x1").is_none());
+    assert!(parse_decimal("1e9999999").is_none());
+  }
+}
@@ -0,0 +1,132 @@
+use num_traits::ToPrimitive;
+use crate::floats::{bits_to_biguint, BitSlice, Float, FloatClass, FloatParameters};
+use crate::printers::{bit2char, field_separator, visible_len, BOLD, CYAN, DARK_GRAY, GREEN, RESET, YELLOW};
+use crate::str_conv::{float_to_exact_str, rational_to_exact_str};
+
+/// Formats with more bits than this have too many encodings to list.
+pub const MAX_TABLE_BITS: usize = 12;
+const PLOT_WIDTH: usize = 64;
+
+fn class_name(class: FloatClass) -> &'static str {
+  match class {
+    FloatClass::PositiveZero | FloatClass::NegativeZero => "zero",
+    FloatClass::PositiveSubnormal | FloatClass::NegativeSubnormal => "subnormal",
+    FloatClass::PositiveNormal | FloatClass::NegativeNormal => "normal",
+    FloatClass::PositiveInf | FloatClass::NegativeInf => "inf",
+    FloatClass::QuietNaN => "qNaN",
+    FloatClass::SignallingNaN => "sNaN",
+  }
+}
+
+fn format_bits(val: &Float) -> String {
+  let bits = |v: &BitSlice| v.iter().rev().map(|v| bit2char(*v)).collect::<String>();
+  format!("{}{}{}{}{}{}{}{}{}", CYAN, bit2char(val.sign()), field_separator(), GREEN, bits(val.exponent_bits()),
+    field_separator(), YELLOW, bits(val.significand_bits()), RESET)
+}
+
+/// Describes the encodings that share a sign and exponent field.
+fn binade_header(val: &Float) -> String {
+  let sign = if val.sign() { "-" } else { "+" };
+  let exp = val.exponent_bits_integer();
+  let params = val.params();
+  let description = if exp == 0 {
+    format!("zero and subnormals, spacing 2^{}", params.min_exp() - params.sig_bits as i64)
+  } else if exp == (1 << params.exp_bits) - 1 {
+    "infinity and NaNs".to_owned()
+  } else {
+    let e = val.exponent_logical();
+    format!("binade [2^{}, 2^{}), spacing 2^{}", e, e + 1, e - params.sig_bits as i64)
+  };
+  format!("{}{} exponent {}: {}{}", BOLD, sign, exp, description, RESET)
+}
+
+/// Prints every encoding of a format, grouped by sign and exponent.
+/// With `plot`, also draws the positive values against their encoding.
+pub fn print_format_table(params: &FloatParameters, plot: bool) {
+  let hex_width = params.total_length().div_ceil(4);
+  let floats = Float::all_encodings(params).collect::<Vec<_>>();
+  let rows = floats.iter().enumerate().map(|(i, val)| {
+    let value = match val.classify() {
+      FloatClass::PositiveInf => "+Inf".to_owned(),
+      FloatClass::NegativeInf => "-Inf".to_owned(),
+      class if class.nan() => "NaN".to_owned(),
+      _ => float_to_exact_str(val),
+    };
+    // the gap to the next encoding, which is the next larger magnitude
+    let gap = floats.get(i + 1)
+      .filter(|next| next.sign() == val.sign())
+      .and_then(|next| Some(next.exact_value()? - val.exact_value()?))
+      .and_then(|v| rational_to_exact_str(&v))
+      .unwrap_or_default();
+    [
+      format_bits(val),
+      format!("0x{:0width$x}", bits_to_biguint(val.bits()), width = hex_width),
+      class_name(val.classify()).to_owned(),
+      value,
+      gap,
+    ]
+  }).collect::<Vec<_>>();
+
+  let header = ["bits", "hex", "class", "value", "gap to next"].map(String::from);
+  let widths = (0..header.len()).map(|i| {
+    rows.iter().chain([&header]).map(|v| visible_len(&v[i])).max().unwrap_or(0)
+  }).collect::<Vec<_>>();
+  let format_row = |row: &[String; 5]| {
+    row.iter().zip(&widths).map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - visible_len(cell))))
+      .collect::<Vec<_>>().join("  ").trim_end().to_owned()
+  };
+
+  println!("{}{}{}", BOLD, format_row(&header), RESET);
+  for (i, (val, row)) in floats.iter().zip(&rows).enumerate() {
+    let new_group = i == 0 || floats[i - 1].exponent_bits() != val.exponent_bits() || floats[i - 1].sign() != val.sign();
+    if new_group {
+      println!();
+      println!("{}", binade_header(val));
+    }
+    println!("{}", format_row(row));
+  }
+
+  if plot {
+    print_plot(params, &floats);
+  }
+}
+
+/// Draws one bar per positive finite encoding, with a length proportional to its value.
+fn print_plot(params: &FloatParameters, floats: &[Float]) {
+  let positive = floats.iter().filter(|v| !v.sign() && v.classify().finite()).collect::<Vec<_>>();
+  let max = Float::max_finite(params, false).exact_value().unwrap();
+  let hex_width = params.total_length().div_ceil(4);
+  println!();
+  println!("{}value against encoding, positive half (the negative half mirrors it){}", BOLD, RESET);
+  for val in positive {
+    let fraction = (val.exact_value().unwrap() / &max).to_f64().unwrap_or(0.0);
+    let len = (fraction * PLOT_WIDTH as f64).round() as usize;
+    let color = if val.classify().normal() { GREEN } else { DARK_GRAY };
+    println!("0x{:0width$x} |{}{}{} {}", bits_to_biguint(val.bits()), color, "#".repeat(len), RESET, float_to_exact_str(val), width = hex_width);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn all_encodings_in_order() {
+    let params = FloatParameters::new(2, 1);
+    let floats = Float::all_encodings(&params).collect::<Vec<_>>();
+    assert_eq!(floats.len(), 16);
+    let classes = floats.iter().take(8).map(|v| class_name(v.classify())).collect::<Vec<_>>();
+    assert_eq!(classes, ["zero", "subnormal", "normal", "normal", "normal", "normal", "inf", "qNaN"]);
+    assert!(floats[8..].iter().all(|v| v.sign()));
+  }
+
+  #[test]
+  fn binade_headers() {
+    let params = FloatParameters::new(3, 2);
+    // sign, 3 exponent bits and 2 significand bits
+    let header = |bits: u32| binade_header(&Float::from_bits(&params, &bits.into()));
+    assert_eq!(header(0b000001), "+ exponent 0: zero and subnormals, spacing 2^-4");
+    assert_eq!(header(0b001100), "+ exponent 3: binade [2^0, 2^1), spacing 2^-2");
+    assert_eq!(header(0b111100), "- exponent 7: infinity and NaNs");
+  }
+}